@@ -0,0 +1,170 @@
+use diol::prelude::*;
+use oxeylyzer_core::config::config::Config;
+use oxeylyzer_core::data_dir::DataFetch;
+use oxeylyzer_core::indexed_data::IndexedData;
+use oxeylyzer_core::layout::layout::Layout;
+use oxeylyzer_core::stats::bigram_stats::{
+    BType,
+    BigramStats,
+};
+use oxeylyzer_core::stats::layout_stats::LayoutStats;
+use oxeylyzer_core::stats::trigram_stats::{
+    TStats,
+    TType,
+};
+use oxeylyzer_core::utility::scored_layout::ScoredLayout;
+use oxeylyzer_core::utility::scorer::Scorer;
+
+const LAYOUTS: [&str; 3] = ["qwerty", "semimak", "colemak_dh"];
+
+fn main() -> std::io::Result<()>
+{
+    let mut bench = Bench::new(BenchConfig::from_args()?);
+
+    bench.register(bigrams_by_position, LAYOUTS);
+    bench.register(bigrams_by_pattern, LAYOUTS);
+    bench.register(trigrams_by_position, LAYOUTS);
+    bench.register(trigrams_by_pattern, LAYOUTS);
+    bench.register(layout_stats, LAYOUTS);
+    bench.register(score, LAYOUTS);
+    bench.register(swap, LAYOUTS);
+
+    bench.run()?;
+
+    return Ok(());
+}
+
+fn load(name: &str) -> (IndexedData, Layout)
+{
+    let data = DataFetch::indexed_data("english");
+    let (mut layouts, _) = DataFetch::load_layouts(DataFetch::layout_files_in_language("english"));
+
+    return (data, layouts.remove(name).unwrap());
+}
+
+/// How the bigram stats were computed before the pattern tables: every pair of positions is
+/// checked and looked up by a formatted string.
+fn bigrams_by_position(bencher: Bencher, name: &str)
+{
+    let (data, layout) = load(name);
+
+    let patterns: [fn(&mut [u8]) -> bool; 7] = [
+        LayoutStats::is_sf,
+        LayoutStats::is_lsb,
+        LayoutStats::is_inroll,
+        LayoutStats::is_outroll,
+        LayoutStats::is_alternate,
+        LayoutStats::is_repeat,
+        LayoutStats::is_scissor,
+    ];
+
+    bencher.bench(|| {
+        let mut res = [0.; 7];
+
+        for (f, total) in patterns.iter().zip(res.iter_mut())
+        {
+            for i in 0 .. 30
+            {
+                for j in 0 .. 30
+                {
+                    if f(&mut [i as u8, j as u8])
+                    {
+                        let bigram = format!("{}{}", layout.matrix[i], layout.matrix[j]);
+
+                        *total += data.language_data.bigrams.get(&bigram).unwrap_or(&0.);
+                    }
+                }
+            }
+        }
+
+        black_box(res);
+    });
+}
+
+fn bigrams_by_pattern(bencher: Bencher, name: &str)
+{
+    let (data, layout) = load(name);
+
+    bencher.bench(|| {
+        black_box(BigramStats::new(&data, &layout.matrix, &BType::ALL));
+    });
+}
+
+/// How the trigram stats were computed before the pattern tables.
+fn trigrams_by_position(bencher: Bencher, name: &str)
+{
+    let (data, layout) = load(name);
+
+    let patterns: [fn(&mut [u8]) -> bool; 4] = [
+        LayoutStats::is_sf,
+        LayoutStats::is_inroll,
+        LayoutStats::is_outroll,
+        LayoutStats::is_redirect,
+    ];
+
+    bencher.bench(|| {
+        let mut res = [0.; 4];
+
+        for i in 0 .. 30
+        {
+            for j in 0 .. 30
+            {
+                for k in 0 .. 30
+                {
+                    for (f, total) in patterns.iter().zip(res.iter_mut())
+                    {
+                        if f(&mut [i as u8, j as u8, k as u8])
+                        {
+                            let c = [layout.matrix[i], layout.matrix[j], layout.matrix[k]];
+                            let trigram = c.iter().collect::<String>();
+
+                            *total += data.language_data.trigrams.get(&trigram).unwrap_or(&0.);
+                        }
+                    }
+                }
+            }
+        }
+
+        black_box(res);
+    });
+}
+
+fn trigrams_by_pattern(bencher: Bencher, name: &str)
+{
+    let (data, layout) = load(name);
+
+    bencher.bench(|| {
+        black_box(TStats::new(&data, &layout.matrix, &TType::ALL));
+    });
+}
+
+fn layout_stats(bencher: Bencher, name: &str)
+{
+    let (data, layout) = load(name);
+
+    bencher.bench(|| {
+        black_box(LayoutStats::new(&data, &layout));
+    });
+}
+
+fn score(bencher: Bencher, name: &str)
+{
+    let (data, layout) = load(name);
+    let scorer = Scorer::new(&Config::default());
+
+    bencher.bench(|| {
+        black_box(scorer.total_score(&data, &layout));
+    });
+}
+
+/// Scoring a swap by only going through the n-grams of the swapped keys.
+fn swap(bencher: Bencher, name: &str)
+{
+    let (data, layout) = load(name);
+    let scorer = Scorer::new(&Config::default());
+    let mut scored = ScoredLayout::new(&data, &scorer, layout);
+
+    bencher.bench(|| {
+        black_box(scored.swap(&data, &scorer, 3, 17));
+    });
+}
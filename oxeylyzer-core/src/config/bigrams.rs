@@ -0,0 +1,91 @@
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+/// The `[bigrams]` section.
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct Bigrams
+{
+    pub same_finger: SameFinger,
+    pub scissors: Scissors,
+    pub rolls: Rolls,
+    pub alternates: Alternates,
+    pub repeats: Repeats,
+}
+
+/// Weights of same finger bigrams and skipgrams, which are weighted by `base * n ^ -decay`,
+/// where `n` is 1 for bigrams, 2 for skipgrams with one key in between and so on.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct SameFinger
+{
+    pub base: f32,
+    /// Used instead of `base` for pinkies and rings.
+    pub bad: f32,
+    pub decay: f32,
+}
+
+impl Default for SameFinger
+{
+    fn default() -> Self
+    {
+        return Self {
+            base: -1.0,
+            bad: -1.25,
+            decay: 1.0,
+        };
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct Scissors
+{
+    pub base: f32,
+    /// Used instead of `base` for scissors with a pinky or ring.
+    pub bad: f32,
+}
+
+impl Default for Scissors
+{
+    fn default() -> Self
+    {
+        return Self {
+            base: 0.0,
+            bad: -2.0,
+        };
+    }
+}
+
+/// Bigrams on the same hand that move towards the index, or away from it.
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct Rolls
+{
+    pub in_roll: f32,
+    pub out_roll: f32,
+}
+
+/// Bigrams that alternate hands.
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct Alternates
+{
+    pub base: f32,
+}
+
+/// Bigrams that press the same key twice.
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct Repeats
+{
+    pub base: f32,
+}
@@ -1,41 +1,230 @@
 use crate::{
+    config::bigrams::Bigrams,
+    config::disjoints::Disjoints,
+    config::effort::Effort,
+    config::error::ConfigError,
     config::finger_to_column::FingerToColumn,
     config::info::Info,
+    config::pins::Pins,
+    config::trigrams::Trigrams,
 };
-use serde::Deserialize;
-use std::fs::File;
-use std::io::Read;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use std::fmt;
+use std::fmt::{
+    Display,
+    Formatter,
+};
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
+
+const CONFIG_FILE: &str = "config.toml";
+/// Environment variable with the path of the config file.
+pub const CONFIG_ENV: &str = "OXEYLYZER_CONFIG";
+
+const WEIGHT_RANGE: RangeInclusive<f32> = -100.0 ..= 100.0;
+const HAND_RANGE: RangeInclusive<f32> = 0.0 ..= 10.0;
+const STRENGTH_RANGE: RangeInclusive<f32> = 0.1 ..= 100.0;
+const DECAY_RANGE: RangeInclusive<f32> = 0.0 ..= 10.0;
+const RATIO_RANGE: RangeInclusive<f32> = 0.0 ..= 10.0;
 
-#[derive(Deserialize, Clone)]
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
 pub struct Config
 {
     pub info: Info,
+    pub pins: Pins,
     pub finger_to_column: FingerToColumn,
+    pub effort: Effort,
+    pub bigrams: Bigrams,
+    pub trigrams: Trigrams,
+    pub disjoints: Disjoints,
 }
 
-impl Default for Config
+/// Where the config was loaded from.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ConfigSource
 {
-    fn default() -> Self
-    {
-        return Self {
-            info: Default::default(),
-            finger_to_column: Default::default(),
-        };
-    }
+    /// The path passed with `--config`.
+    Flag(PathBuf),
+    /// The path in `OXEYLYZER_CONFIG`.
+    Env(PathBuf),
+    /// `oxeylyzer/config.toml` in the XDG config directory.
+    Xdg(PathBuf),
+    /// `config.toml` in the working directory.
+    WorkingDirectory(PathBuf),
+    /// No config file was found.
+    Default,
 }
 
 impl Config
 {
-    pub fn new() -> Self
+    /// Loads the config from the first of these that is set or exists: the `--config` path,
+    /// `OXEYLYZER_CONFIG`, `$XDG_CONFIG_HOME/oxeylyzer/config.toml` (or `~/.config` when it isn't
+    /// set) and `config.toml` in the working directory. Without any, the defaults are used.
+    pub fn load(flag: Option<&str>) -> Result<(Self, ConfigSource), ConfigError>
     {
-        let mut f = File::open("../../../../config.toml").expect("config.toml is missing.");
+        let source = Self::find(flag);
+
+        let config = match source.path()
+        {
+            | None => Self::default(),
+            | Some(path) => Self::from_file(path)?,
+        };
+
+        return Ok((config, source));
+    }
+
+    /// Reads and validates a config file. Unknown sections and keys are errors, as are numbers
+    /// outside of the range they should be in.
+    pub fn from_file(path: &PathBuf) -> Result<Self, ConfigError>
+    {
+        let buf = std::fs::read_to_string(path).map_err(|e| {
+            ConfigError::Read {
+                path: path.clone(),
+                message: e.to_string(),
+            }
+        })?;
+
+        let config: Self =
+            toml::from_str(&buf).map_err(|e| ConfigError::parse(path.clone(), &buf, e))?;
+
+        for (key, value, range) in config.ranges()
+        {
+            if !range.contains(&value)
+            {
+                return Err(ConfigError::Range {
+                    path: path.clone(),
+                    line: ConfigError::line_of(&buf, key),
+                    key: key.to_string(),
+                    value,
+                    expected: format!("between {} and {}", range.start(), range.end()),
+                });
+            }
+        }
+
+        return Ok(config);
+    }
+
+    /// Every number in the config with the range it should be in.
+    fn ranges(&self) -> Vec<(&'static str, f32, RangeInclusive<f32>)>
+    {
+        let preference = &self.effort.fingers.preference;
+        let fspeed = &self.effort.fspeed;
+        let bigrams = &self.bigrams;
+        let disjoints = &self.disjoints;
+
+        return vec![
+            ("effort.heatmap", self.effort.heatmap, WEIGHT_RANGE),
+            ("effort.layer_activation", self.effort.layer_activation, WEIGHT_RANGE),
+            ("effort.hand.left", self.effort.hand.left, HAND_RANGE),
+            ("effort.hand.right", self.effort.hand.right, HAND_RANGE),
+            ("effort.hand.weight", self.effort.hand.weight, WEIGHT_RANGE),
+            ("effort.fingers.lateral_move", self.effort.fingers.lateral_move, WEIGHT_RANGE),
+            ("effort.fingers.overuse", self.effort.fingers.overuse, WEIGHT_RANGE),
+            ("effort.fingers.preference.pinky", preference.pinky, STRENGTH_RANGE),
+            ("effort.fingers.preference.ring", preference.ring, STRENGTH_RANGE),
+            ("effort.fingers.preference.middle", preference.middle, STRENGTH_RANGE),
+            ("effort.fingers.preference.index", preference.index, STRENGTH_RANGE),
+            ("effort.fspeed.lateral_penalty", fspeed.lateral_penalty, RATIO_RANGE),
+            ("effort.fspeed.dsfb_ratio", fspeed.dsfb_ratio, RATIO_RANGE),
+            ("effort.fspeed.skip_decay", fspeed.skip_decay, DECAY_RANGE),
+            ("bigrams.same_finger.base", self.bigrams.same_finger.base, WEIGHT_RANGE),
+            ("bigrams.same_finger.bad", self.bigrams.same_finger.bad, WEIGHT_RANGE),
+            ("bigrams.same_finger.decay", self.bigrams.same_finger.decay, DECAY_RANGE),
+            ("bigrams.scissors.base", self.bigrams.scissors.base, WEIGHT_RANGE),
+            ("bigrams.scissors.bad", self.bigrams.scissors.bad, WEIGHT_RANGE),
+            ("bigrams.rolls.in_roll", bigrams.rolls.in_roll, WEIGHT_RANGE),
+            ("bigrams.rolls.out_roll", bigrams.rolls.out_roll, WEIGHT_RANGE),
+            ("bigrams.alternates.base", bigrams.alternates.base, WEIGHT_RANGE),
+            ("bigrams.repeats.base", bigrams.repeats.base, WEIGHT_RANGE),
+            ("trigrams.alternates.base", self.trigrams.alternates.base, WEIGHT_RANGE),
+            ("trigrams.redirects.base", self.trigrams.redirects.base, WEIGHT_RANGE),
+            ("trigrams.redirects.bad", self.trigrams.redirects.bad, WEIGHT_RANGE),
+            ("trigrams.rolls.in_roll", self.trigrams.rolls.in_roll, WEIGHT_RANGE),
+            ("trigrams.rolls.out_roll", self.trigrams.rolls.out_roll, WEIGHT_RANGE),
+            ("trigrams.one_hands.base", self.trigrams.one_hands.base, WEIGHT_RANGE),
+            ("trigrams.same_finger.sft", self.trigrams.same_finger.sft, WEIGHT_RANGE),
+            ("trigrams.same_finger.sfbt", self.trigrams.same_finger.sfbt, WEIGHT_RANGE),
+            ("disjoints.sfb", disjoints.sfb, WEIGHT_RANGE),
+            ("disjoints.lsb", disjoints.lsb, WEIGHT_RANGE),
+            ("disjoints.in_roll", disjoints.in_roll, WEIGHT_RANGE),
+            ("disjoints.out_roll", disjoints.out_roll, WEIGHT_RANGE),
+            ("disjoints.repeat", disjoints.repeat, WEIGHT_RANGE),
+            ("disjoints.scissors", disjoints.scissors, WEIGHT_RANGE),
+        ];
+    }
+
+    fn find(flag: Option<&str>) -> ConfigSource
+    {
+        if let Some(path) = flag
+        {
+            return ConfigSource::Flag(PathBuf::from(path));
+        }
+
+        if let Ok(path) = std::env::var(CONFIG_ENV)
+        {
+            return ConfigSource::Env(PathBuf::from(path));
+        }
+
+        let xdg = std::env::var("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|_| std::env::var("HOME").map(|h| PathBuf::from(h).join(".config")));
+
+        if let Ok(dir) = xdg
+        {
+            let path = dir.join("oxeylyzer").join(CONFIG_FILE);
+
+            if path.is_file()
+            {
+                return ConfigSource::Xdg(path);
+            }
+        }
 
-        let mut buf = String::new();
-        f.read_to_string(&mut buf).unwrap();
+        let path = PathBuf::from(CONFIG_FILE);
 
-        let new: Self = toml::from_str(&buf).expect("Failed to parse config.toml.");
+        if path.is_file()
+        {
+            return ConfigSource::WorkingDirectory(path);
+        }
 
-        return new;
+        return ConfigSource::Default;
+    }
+}
+
+impl ConfigSource
+{
+    pub fn path(&self) -> Option<&PathBuf>
+    {
+        return match self
+        {
+            | ConfigSource::Flag(p)
+            | ConfigSource::Env(p)
+            | ConfigSource::Xdg(p)
+            | ConfigSource::WorkingDirectory(p) => Some(p),
+            | ConfigSource::Default => None,
+        };
+    }
+}
+
+impl Display for ConfigSource
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result
+    {
+        return match self
+        {
+            | ConfigSource::Flag(p) => write!(f, "{} (--config)", p.display()),
+            | ConfigSource::Env(p) => write!(f, "{} (${CONFIG_ENV})", p.display()),
+            | ConfigSource::Xdg(p) => write!(f, "{} (config directory)", p.display()),
+            | ConfigSource::WorkingDirectory(p) =>
+            {
+                write!(f, "{} (working directory)", p.display())
+            },
+            | ConfigSource::Default => write!(f, "the defaults, no config file was found"),
+        };
     }
 }
 
@@ -44,3 +233,38 @@ fn test()
 {
     Config::default();
 }
+
+#[test]
+fn parse_config_toml()
+{
+    let config = Config::from_file(&PathBuf::from(format!("../{CONFIG_FILE}"))).unwrap();
+
+    assert_eq!(config.finger_to_column.finger_to_column.len(), 40);
+    assert!(config.pins.is_pinned(7));
+    assert_eq!(config.trigrams.rolls.in_roll, 3.0);
+    assert_eq!(config.effort.hand.weight, -1.0);
+    assert_eq!(config.disjoints.sfb, 0.0);
+
+    let serialized = toml::to_string(&config).unwrap();
+
+    assert_eq!(toml::from_str::<Config>(&serialized).unwrap(), config);
+}
+
+#[test]
+fn config_errors()
+{
+    let buf = "[info]\nlanguage = \"english\"\n\n[brigams.same_finger]\nbase = -1\n";
+
+    match ConfigError::parse(PathBuf::new(), buf, toml::from_str::<Config>(buf).unwrap_err())
+    {
+        | ConfigError::Parse { line, suggestion, .. } =>
+        {
+            assert_eq!(line, Some(4));
+            assert_eq!(suggestion.as_deref(), Some("bigrams"));
+        },
+        | e => panic!("{e}"),
+    }
+
+    assert_eq!(ConfigError::line_of(buf, "info.language"), Some(2));
+    assert!(toml::from_str::<Config>("[info]\nkeyboard_type = \"ansii\"").is_err());
+}
@@ -0,0 +1,37 @@
+use crate::stats::disjoint_stats::DType;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+/// The `[disjoints]` section: weights of the bigrams made by the first and last key of trigrams
+/// that alternate hands twice. The skipgram stats already count these as well, so this is on
+/// top of that.
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct Disjoints
+{
+    pub sfb: f32,
+    pub lsb: f32,
+    pub in_roll: f32,
+    pub out_roll: f32,
+    pub repeat: f32,
+    pub scissors: f32,
+}
+
+impl Disjoints
+{
+    pub const fn of(&self, t: DType) -> f32
+    {
+        return match t
+        {
+            | DType::D1SFB => self.sfb,
+            | DType::D1LSB => self.lsb,
+            | DType::D1IRB => self.in_roll,
+            | DType::D1ORB => self.out_roll,
+            | DType::D1Repeat => self.repeat,
+            | DType::D1S => self.scissors,
+        };
+    }
+}
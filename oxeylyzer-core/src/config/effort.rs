@@ -0,0 +1,155 @@
+use crate::hand::finger::Finger;
+use crate::hand::finger::Finger::*;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+/// The `[effort]` section.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct Effort
+{
+    /// Weight of the heatmap, the frequency of every key times the effort of its position.
+    pub heatmap: f32,
+    /// Weight of how often layer keys have to be pressed, relative to all keypresses.
+    pub layer_activation: f32,
+    pub hand: HandEffort,
+    pub fingers: FingerEffort,
+    pub fspeed: Fspeed,
+}
+
+impl Default for Effort
+{
+    fn default() -> Self
+    {
+        return Self {
+            heatmap: -0.1,
+            layer_activation: -1.0,
+            hand: HandEffort::default(),
+            fingers: FingerEffort::default(),
+            fspeed: Fspeed::default(),
+        };
+    }
+}
+
+/// How much each hand should be used relative to the other.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct HandEffort
+{
+    pub left: f32,
+    pub right: f32,
+    /// Weight of how far the hand usage is from the ratio of `left` and `right`.
+    pub weight: f32,
+}
+
+impl Default for HandEffort
+{
+    fn default() -> Self
+    {
+        return Self {
+            left: 1.0,
+            right: 1.0,
+            weight: -1.0,
+        };
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct FingerEffort
+{
+    /// Weight of moving a finger sideways to another column.
+    pub lateral_move: f32,
+    /// Weight of how much the fingers are used more than their share by `preference`.
+    pub overuse: f32,
+    pub preference: FingerPreference,
+}
+
+impl Default for FingerEffort
+{
+    fn default() -> Self
+    {
+        return Self {
+            lateral_move: -2.0,
+            overuse: -1.0,
+            preference: FingerPreference::default(),
+        };
+    }
+}
+
+/// The strength of each finger, the same for both hands. Higher is stronger.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct FingerPreference
+{
+    pub pinky: f32,
+    pub ring: f32,
+    pub middle: f32,
+    pub index: f32,
+}
+
+impl FingerPreference
+{
+    /// The strength of a finger. Thumbs don't have one, as they have no keys on the layout.
+    pub const fn of(&self, finger: Finger) -> f32
+    {
+        return match finger
+        {
+            | LP | RP => self.pinky,
+            | LR | RR => self.ring,
+            | LM | RM => self.middle,
+            | LI | RI => self.index,
+            | LT | RT => 0.,
+        };
+    }
+
+    pub fn strongest(&self) -> f32
+    {
+        return self.pinky.max(self.ring).max(self.middle).max(self.index);
+    }
+}
+
+impl Default for FingerPreference
+{
+    fn default() -> Self
+    {
+        return Self {
+            pinky: 1.0,
+            ring: 14.0,
+            middle: 20.0,
+            index: 20.0,
+        };
+    }
+}
+
+/// How finger speed weighs the distance a finger travels between same finger keys.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct Fspeed
+{
+    /// Multiplies the sideways part of the distance.
+    pub lateral_penalty: f32,
+    /// Weight of skipgrams with one key in between compared to bigrams.
+    pub dsfb_ratio: f32,
+    /// Skipgrams with `n` keys in between are weighted by `dsfb_ratio * n ^ -skip_decay`.
+    pub skip_decay: f32,
+}
+
+impl Default for Fspeed
+{
+    fn default() -> Self
+    {
+        return Self {
+            lateral_penalty: 1.0,
+            dsfb_ratio: 0.11,
+            skip_decay: 1.0,
+        };
+    }
+}
@@ -0,0 +1,185 @@
+use itertools::Itertools;
+use std::fmt;
+use std::fmt::{
+    Display,
+    Formatter,
+};
+use std::path::PathBuf;
+
+/// Why a config file couldn't be used.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ConfigError
+{
+    Read
+    {
+        path: PathBuf,
+        message: String,
+    },
+    /// The file isn't valid TOML, or doesn't match the config schema.
+    Parse
+    {
+        path: PathBuf,
+        line: Option<usize>,
+        message: String,
+        suggestion: Option<String>,
+    },
+    /// A value is outside of the range it is allowed to be in.
+    Range
+    {
+        path: PathBuf,
+        line: Option<usize>,
+        key: String,
+        value: f32,
+        expected: String,
+    },
+}
+
+impl ConfigError
+{
+    /// Turns a TOML error into a parse error, with the line it happened on, and a suggestion if
+    /// it's about an unknown key that is close to a known one.
+    pub fn parse(path: PathBuf, source: &str, error: toml::de::Error) -> Self
+    {
+        let line = error.span().map(|s| Self::line_at(source, s.start));
+        let message = error.message().trim().to_string();
+
+        let suggestion = match message.starts_with("unknown field")
+        {
+            | false => None,
+            | true =>
+            {
+                // serde lists the known fields like "unknown field `x`, expected one of `a`, `b`".
+                let names = message.split('`').skip(1).step_by(2).collect_vec();
+
+                names
+                    .split_first()
+                    .and_then(|(unknown, expected)| Self::closest(unknown, expected))
+            },
+        };
+
+        return ConfigError::Parse {
+            path,
+            line,
+            message,
+            suggestion,
+        };
+    }
+
+    fn line_at(source: &str, offset: usize) -> usize
+    {
+        return source[.. offset.min(source.len())].matches('\n').count() + 1;
+    }
+
+    /// The line a key is set on, e.g. `bigrams.scissors.bad`.
+    pub fn line_of(source: &str, key: &str) -> Option<usize>
+    {
+        let (table, name) = key.rsplit_once('.')?;
+
+        let mut current = "";
+
+        for (i, line) in source.lines().enumerate()
+        {
+            let line = line.trim();
+
+            if line.starts_with('[') && line.ends_with(']')
+            {
+                current = line[1 .. line.len() - 1].trim();
+            }
+            else if let Some((k, _)) = line.split_once('=')
+            {
+                if current == table && k.trim() == name
+                {
+                    return Some(i + 1);
+                }
+            }
+        }
+
+        return None;
+    }
+
+    fn closest(unknown: &str, expected: &[&str]) -> Option<String>
+    {
+        return expected
+            .iter()
+            .map(|e| (Self::distance(unknown, e), e))
+            .filter(|(d, _)| *d <= 2.max(unknown.len() / 3))
+            .min_by_key(|(d, _)| *d)
+            .map(|(_, e)| e.to_string());
+    }
+
+    /// The Levenshtein distance between two strings.
+    fn distance(a: &str, b: &str) -> usize
+    {
+        let b = b.chars().collect_vec();
+        let mut row = (0 ..= b.len()).collect_vec();
+
+        for (i, ca) in a.chars().enumerate()
+        {
+            let mut previous = row[0];
+            row[0] = i + 1;
+
+            for (j, cb) in b.iter().enumerate()
+            {
+                let substitution = previous + if ca == *cb { 0 } else { 1 };
+
+                previous = row[j + 1];
+                row[j + 1] = substitution.min(row[j] + 1).min(row[j + 1] + 1);
+            }
+        }
+
+        return row[b.len()];
+    }
+}
+
+impl Display for ConfigError
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result
+    {
+        let location = |path: &PathBuf, line: &Option<usize>| {
+            match line
+            {
+                | Some(l) => format!("{}:{l}", path.display()),
+                | None => format!("{}", path.display()),
+            }
+        };
+
+        return match self
+        {
+            | ConfigError::Read { path, message } =>
+            {
+                write!(f, "Couldn't read {}: {message}", path.display())
+            },
+            | ConfigError::Parse {
+                path,
+                line,
+                message,
+                suggestion,
+            } =>
+            {
+                write!(f, "Invalid config at {}: {message}", location(path, line))?;
+
+                match suggestion
+                {
+                    | Some(s) => write!(f, "\nDid you mean `{s}`?"),
+                    | None => Ok(()),
+                }
+            },
+            | ConfigError::Range {
+                path,
+                line,
+                key,
+                value,
+                expected,
+            } =>
+            {
+                write!(
+                    f,
+                    "Invalid config at {}: `{key}` is {value}, it should be {expected}.",
+                    location(path, line)
+                )
+            },
+        };
+    }
+}
+
+impl std::error::Error for ConfigError {}
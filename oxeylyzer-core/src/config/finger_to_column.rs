@@ -1,18 +1,71 @@
 use crate::hand::finger::Finger;
-use serde::Deserialize;
+use itertools::Itertools;
+use serde::{
+    Deserialize,
+    Deserializer,
+    Serialize,
+    Serializer,
+};
 use std::ops;
 
-#[derive(Deserialize, Clone)]
-#[serde(from = "String")]
+const DEFAULT_GRID: &str = "\
+01233  66789
+01233  66789
+01233  66789
+44444  55555";
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+#[serde(deny_unknown_fields)]
 pub struct FingerToColumn {
+    #[serde(deserialize_with = "FingerToColumn::deserialize_grid")]
+    #[serde(serialize_with = "FingerToColumn::serialize_grid")]
     pub finger_to_column: Vec<Finger>,
 }
 
 impl Default for FingerToColumn {
     fn default() -> Self {
-        return Self {
-            finger_to_column: Vec::new(),
-        };
+        return Self::from(DEFAULT_GRID.to_string());
+    }
+}
+
+impl FingerToColumn {
+    fn deserialize_grid<'de, D>(deserializer: D) -> Result<Vec<Finger>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let grid = String::deserialize(deserializer)?;
+        let digits = grid.chars().filter(|c| !c.is_whitespace()).collect_vec();
+
+        if let Some(c) = digits.iter().find(|c| !matches!(c, '0' ..= '9')) {
+            return Err(serde::de::Error::custom(format!(
+                "'{c}' is not a finger, use the numbers 0 to 9."
+            )));
+        }
+
+        if digits.len() < 30 || digits.len() % 10 != 0 {
+            return Err(serde::de::Error::custom(format!(
+                "The finger to column grid has {} keys, it needs rows of 10 and at least 30.",
+                digits.len()
+            )));
+        }
+
+        return Ok(Self::from(grid).finger_to_column);
+    }
+
+    fn serialize_grid<S>(fingers: &[Finger], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let grid = fingers
+            .chunks(10)
+            .map(|row| {
+                let row = row.iter().map(|f| (*f as u8).to_string()).collect::<String>();
+
+                format!("{}  {}", &row[.. 5], &row[5 ..])
+            })
+            .join("\n");
+
+        return serializer.serialize_str(format!("{grid}\n").as_str());
     }
 }
 
@@ -28,7 +81,7 @@ impl From<String> for FingerToColumn {
     fn from(value: String) -> Self {
         let mut finger_to_column = Vec::new();
 
-        let value = value.trim().replace([' ', '\n'], "");
+        let value = value.trim().replace([' ', '\n', '\r'], "");
 
         for c in value.chars().into_iter() {
             finger_to_column.push(Finger::from(c.to_digit(10).unwrap() as u8));
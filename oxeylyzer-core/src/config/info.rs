@@ -1,7 +1,12 @@
 use crate::layout::keyboard_type::KeyboardType;
-use serde::Deserialize;
+use serde::{
+    Deserialize,
+    Serialize,
+};
 
-#[derive(Deserialize, Clone)]
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
 pub struct Info {
     pub language: String,
     pub keyboard_type: KeyboardType,
@@ -1,3 +1,9 @@
+pub mod bigrams;
 pub mod config;
-mod finger_to_column;
+pub mod disjoints;
+pub mod effort;
+pub mod error;
+pub mod finger_to_column;
 pub mod info;
+pub mod pins;
+pub mod trigrams;
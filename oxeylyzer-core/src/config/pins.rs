@@ -0,0 +1,162 @@
+use crate::type_def::Fixed;
+use itertools::Itertools;
+use serde::{
+    Deserialize,
+    Deserializer,
+    Serialize,
+    Serializer,
+};
+use std::fmt;
+use std::fmt::{
+    Display,
+    Formatter,
+};
+
+const PINNED: char = 'x';
+const FREE: char = '.';
+
+/// Positions of a layout that layout searches aren't allowed to move.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Pins
+{
+    #[serde(deserialize_with = "Pins::deserialize_grid")]
+    #[serde(serialize_with = "Pins::serialize_grid")]
+    pub pins: Fixed<bool>,
+}
+
+impl Default for Pins
+{
+    fn default() -> Self
+    {
+        return Self { pins: [false; 30] };
+    }
+}
+
+impl Pins
+{
+    /// Reads a grid of `.` and `x`, where `x` is pinned. It should have 3 rows of 10 keys,
+    /// optionally followed by a row of thumb keys, which isn't part of the layout and is ignored.
+    pub fn parse_grid(grid: &str) -> Result<Fixed<bool>, String>
+    {
+        let rows = grid
+            .lines()
+            .map(|l| l.chars().filter(|c| !c.is_whitespace()).collect_vec())
+            .filter(|r| !r.is_empty())
+            .collect_vec();
+
+        if rows.len() < 3 || rows.len() > 4
+        {
+            return Err(format!(
+                "The pins have {} rows, 3 required and an optional thumb row.",
+                rows.len()
+            ));
+        }
+
+        let mut pins = [false; 30];
+
+        for (r, row) in rows.iter().enumerate()
+        {
+            if r < 3 && row.len() != 10
+            {
+                return Err(format!(
+                    "Row {} of the pins has {} keys, 10 required.",
+                    r + 1,
+                    row.len()
+                ));
+            }
+
+            for (i, c) in row.iter().enumerate()
+            {
+                let pinned = match *c
+                {
+                    | PINNED => true,
+                    | FREE => false,
+                    | c => return Err(format!("'{c}' is not a pin, use '{PINNED}' or '{FREE}'.")),
+                };
+
+                if r < 3
+                {
+                    pins[r * 10 + i] = pinned;
+                }
+            }
+        }
+
+        return Ok(pins);
+    }
+
+    fn deserialize_grid<'de, D>(deserializer: D) -> Result<Fixed<bool>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let grid = String::deserialize(deserializer)?;
+
+        return Self::parse_grid(grid.as_str()).map_err(serde::de::Error::custom);
+    }
+
+    fn serialize_grid<S>(pins: &Fixed<bool>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let grid = Self { pins: *pins }.to_string();
+
+        return serializer.serialize_str(format!("{grid}\n").as_str());
+    }
+
+    #[inline(always)]
+    pub fn is_pinned(&self, position: usize) -> bool
+    {
+        return self.pins[position];
+    }
+
+    pub fn toggle(&mut self, position: usize)
+    {
+        self.pins[position] = !self.pins[position];
+    }
+
+    /// The positions that can be moved.
+    pub fn free(&self) -> Vec<usize>
+    {
+        return (0 .. 30).filter(|i| !self.pins[*i]).collect_vec();
+    }
+
+    /// Every swap of two positions that leaves the pinned ones in place.
+    pub fn swaps(&self) -> Vec<(usize, usize)>
+    {
+        return self.free().into_iter().tuple_combinations().collect_vec();
+    }
+}
+
+impl Display for Pins
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result
+    {
+        let rows = self
+            .pins
+            .chunks(10)
+            .map(|row| {
+                let marks = row
+                    .iter()
+                    .map(|p| if *p { PINNED } else { FREE })
+                    .collect::<String>();
+
+                format!("{}  {}", &marks[.. 5], &marks[5 ..])
+            })
+            .join("\n");
+
+        write!(f, "{}", rows)
+    }
+}
+
+#[test]
+fn parse_pins()
+{
+    let pins = Pins::parse_grid(".....  ..xx.\n...x.  .xxxx\n.....  ..x..\n     xx\n").unwrap();
+
+    assert!(pins[7] && pins[8] && pins[13] && pins[27]);
+    assert_eq!(pins.iter().filter(|p| **p).count(), 8);
+
+    assert!(Pins::parse_grid(".....  ..xx.\n...x.  .xxxx\n").is_err());
+    assert!(Pins::parse_grid(".....  ..xx\n...x.  .xxxx\n.....  ..x..").is_err());
+    assert!(Pins::parse_grid(".....  ..xx.\n...x.  .xxxx\n.....  ..o..").is_err());
+}
@@ -0,0 +1,93 @@
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+/// The `[trigrams]` section.
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct Trigrams
+{
+    pub alternates: Alternates,
+    pub redirects: Redirects,
+    pub rolls: Rolls,
+    pub one_hands: OneHands,
+    pub same_finger: SameFinger,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct Alternates
+{
+    pub base: f32,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct Redirects
+{
+    pub base: f32,
+    /// Redirects without an index finger.
+    pub bad: f32,
+}
+
+impl Default for Redirects
+{
+    fn default() -> Self
+    {
+        return Self {
+            base: 0.0,
+            bad: -2.0,
+        };
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct Rolls
+{
+    pub in_roll: f32,
+    pub out_roll: f32,
+}
+
+impl Default for Rolls
+{
+    fn default() -> Self
+    {
+        return Self {
+            in_roll: 3.0,
+            out_roll: 2.0,
+        };
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct OneHands
+{
+    pub base: f32,
+}
+
+impl Default for OneHands
+{
+    fn default() -> Self
+    {
+        return Self { base: 1.0 };
+    }
+}
+
+/// Same finger trigrams and trigrams with a same finger bigram, which the same finger bigram
+/// stats already count, so this is on top of that.
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct SameFinger
+{
+    pub sft: f32,
+    pub sfbt: f32,
+}
@@ -0,0 +1,111 @@
+use itertools::Itertools;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+#[derive(Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct CharMapping
+{
+    pub from: String,
+    pub to: String,
+}
+
+impl CharMapping
+{
+    pub fn pairs(&self) -> Vec<(char, char)>
+    {
+        return self.from.chars().zip(self.to.chars()).collect_vec();
+    }
+
+    fn extend(&mut self, other: &Self)
+    {
+        self.from.push_str(other.from.as_str());
+        self.to.push_str(other.to.as_str());
+    }
+}
+
+#[derive(Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct ToMultiple
+{
+    pub list: Vec<(String, String)>,
+    pub uppercase_versions: bool,
+}
+
+/// The rules in a `static/corpus_configs` file, describing how a corpus was turned into language data.
+#[derive(Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct CorpusConfig
+{
+    pub inherits: Vec<String>,
+    pub letters_to_lowercase: String,
+    pub punct_unshifted: CharMapping,
+    pub one_to_one: CharMapping,
+    pub to_multiple: ToMultiple,
+}
+
+impl CorpusConfig
+{
+    /// Merges the rules of an inherited config into this one. Rules defined here come first, so
+    /// they win when both configs map the same character.
+    pub fn inherit(&mut self, parent: &Self)
+    {
+        self.letters_to_lowercase
+            .push_str(parent.letters_to_lowercase.as_str());
+        self.punct_unshifted.extend(&parent.punct_unshifted);
+        self.one_to_one.extend(&parent.one_to_one);
+        self.to_multiple
+            .list
+            .extend(parent.to_multiple.list.iter().cloned());
+    }
+
+    /// Maps every unshifted character to what it types with shift held, based on
+    /// `letters_to_lowercase` and `punct_unshifted`.
+    pub fn shift_pairs(&self) -> HashMap<char, char>
+    {
+        let mut pairs = HashMap::new();
+
+        for c in self.letters_to_lowercase.chars()
+        {
+            let mut upper = c.to_uppercase();
+
+            if let (Some(u), None) = (upper.next(), upper.next())
+            {
+                pairs.entry(c).or_insert(u);
+            }
+        }
+
+        for (shifted, unshifted) in self.punct_unshifted.pairs()
+        {
+            pairs.entry(unshifted).or_insert(shifted);
+        }
+
+        return pairs;
+    }
+}
+
+#[test]
+fn shift_pairs()
+{
+    let config: CorpusConfig = toml::from_str(
+        r#"
+        letters_to_lowercase = "abß"
+
+        [punct_unshifted]
+        from = "?:\"“"
+        to = "/;''"
+        "#,
+    )
+    .unwrap();
+
+    let pairs = config.shift_pairs();
+
+    assert_eq!(pairs[&'a'], 'A');
+    assert_eq!(pairs[&'b'], 'B');
+    assert_eq!(pairs[&'/'], '?');
+    assert_eq!(pairs[&';'], ':');
+    // The first shifted character of a key wins, and ß has no single uppercase character.
+    assert_eq!(pairs[&'\''], '"');
+    assert!(!pairs.contains_key(&'ß'));
+    assert_eq!(pairs.len(), 5);
+}
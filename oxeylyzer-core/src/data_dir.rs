@@ -1,7 +1,23 @@
 use crate::type_def::Fixed;
 use itertools::Itertools;
 
+use crate::corpus_config::CorpusConfig;
+use crate::export::template::BoardTemplates;
+use crate::hand::finger::Finger;
+use crate::import::cmini::Cmini;
+use crate::import::klc::Klc;
+use crate::import::kle::Kle;
+use crate::import::report::ImportReport;
+use crate::indexed_data::IndexedData;
+use crate::language_data::LanguageData;
+use crate::layout::keyboard_type::KeyboardType;
+use crate::layout::layer::{
+    Activation,
+    Layer,
+    EMPTY_KEY,
+};
 use crate::layout::layout::Layout;
+use crate::layout::metadata::Metadata;
 use std::collections::HashMap;
 use std::fs::{
     File,
@@ -11,6 +27,9 @@ use std::io::Read;
 use std::path::PathBuf;
 
 const ROOT: &str = "static";
+const HEADER_DELIMITER: &str = "---";
+const EMPTY_TOKEN: &str = "__";
+
 pub struct DataFetch {}
 
 impl DataFetch
@@ -38,43 +57,366 @@ impl DataFetch
         return Self::files_in(paths);
     }
 
-    pub fn load_layouts(fetch: ReadDir) -> HashMap<String, Layout>
+    /// Loads every `.kb` file, by name. Files that can't be read or parsed are skipped, and
+    /// returned as errors instead.
+    pub fn load_layouts(fetch: ReadDir) -> (HashMap<String, Layout>, Vec<String>)
     {
         use std::fs::read_to_string;
 
         let mut layouts = HashMap::new();
+        let mut errors = Vec::new();
 
         for entry in fetch.flatten().into_iter()
         {
-            if entry.path().extension().unwrap() != "kb"
+            let path = entry.path();
+
+            if path.extension().is_none_or(|e| e != "kb")
             {
                 continue;
             }
 
-            let string = read_to_string(entry.path());
-
-            let name = entry.file_name().to_str().unwrap().to_string();
+            let name = entry.file_name().to_string_lossy().to_string();
             let name = name[.. name.len() - 3].to_string();
 
-            layouts.insert(name, Self::parse_layout(&string.unwrap().as_str()));
+            let layout = read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|string| Self::parse_layout(string.as_str()));
+
+            match layout
+            {
+                | Ok(layout) =>
+                {
+                    layouts.insert(name, layout);
+                },
+                | Err(e) => errors.push(format!("{}: {e}", path.display())),
+            }
         }
 
-        return layouts;
+        return (layouts, errors);
     }
 
-    pub fn parse_layout(string: &str) -> Layout
+    /// Parses a `.kb` file. A v2 file starts with a TOML header block between two `---` lines,
+    /// see `Metadata`. The base layer is a grid of 30 keys, optionally followed by extra layers,
+    /// each introduced by a `[<name> <hold|oneshot> <finger>]` line, e.g. `[sym hold RT]`.
+    /// Keys are separated by whitespace, and `__` marks an empty slot.
+    pub fn parse_layout(string: &str) -> Result<Layout, String>
     {
-        let mut layout = [' '; 30];
+        let (metadata, body) = Self::split_header(string)?;
 
-        string
-            .to_string()
-            .chars()
-            .filter(|x| !x.is_whitespace())
-            .into_iter()
-            .enumerate()
-            .for_each(|(i, c)| layout[i] = c);
+        let lines = body.lines().collect_vec();
+        let split = lines
+            .iter()
+            .position(|l| Self::is_layer_header(l))
+            .unwrap_or(lines.len());
+
+        let (matrix, extra) = Self::parse_grid(&lines[.. split]);
+        let mut layout = Layout::from(matrix);
+
+        layout.extra = extra;
+        layout.layers = Self::parse_layers(&lines[split ..], &mut layout.extra)?;
+        layout.metadata = metadata;
+
+        return Ok(layout);
+    }
+
+    fn split_header(string: &str) -> Result<(Metadata, &str), String>
+    {
+        let mut offset = 0;
+        let mut header_start = None;
+
+        for line in string.split_inclusive('\n')
+        {
+            offset += line.len();
+
+            if line.trim() != HEADER_DELIMITER
+            {
+                if header_start.is_none()
+                {
+                    return Ok((Metadata::default(), string));
+                }
+
+                continue;
+            }
+
+            match header_start
+            {
+                | None => header_start = Some(offset),
+                | Some(start) =>
+                {
+                    let header = &string[start .. offset - line.len()];
+
+                    let metadata = toml::from_str(header)
+                        .map_err(|e| format!("Invalid layout header: {}", e.message().trim()))?;
+
+                    return Ok((metadata, &string[offset ..]));
+                },
+            }
+        }
+
+        return Err(format!("The layout header is missing its closing '{HEADER_DELIMITER}'."));
+    }
+
+    /// The first 30 keys of a grid, and the ones after them.
+    fn parse_grid(lines: &[&str]) -> (Fixed<char>, Vec<char>)
+    {
+        let mut grid = [EMPTY_KEY; 30];
+
+        let mut keys = lines
+            .iter()
+            .flat_map(|l| l.split_whitespace())
+            .flat_map(|token| {
+                if token == EMPTY_TOKEN
+                {
+                    return vec![EMPTY_KEY];
+                }
+
+                return token.chars().collect_vec();
+            })
+            .collect_vec();
+
+        let extra = keys.split_off(keys.len().min(30));
+        let extra = extra.into_iter().filter(|c| *c != EMPTY_KEY).collect_vec();
+
+        keys.into_iter().enumerate().for_each(|(i, c)| grid[i] = c);
+
+        return (grid, extra);
+    }
+
+    fn is_layer_header(line: &str) -> bool
+    {
+        let line = line.trim();
+
+        if !(line.starts_with('[') && line.ends_with(']'))
+        {
+            return false;
+        }
+
+        let inner = line[1 .. line.len() - 1].split_whitespace().collect_vec();
+
+        return inner.len() == 3 && inner[1].parse::<Activation>().is_ok();
+    }
+
+    /// The layers after the base grid. Keys past the 30 of a layer are added to `extra`.
+    fn parse_layers(lines: &[&str], extra: &mut Vec<char>) -> Result<Vec<Layer>, String>
+    {
+        let mut layers = Vec::new();
+
+        let headers = lines
+            .iter()
+            .positions(|l| Self::is_layer_header(l))
+            .collect_vec();
+
+        for (n, start) in headers.iter().enumerate()
+        {
+            let end = headers.get(n + 1).copied().unwrap_or(lines.len());
+
+            let line = lines[*start].trim();
+            let inner = line[1 .. line.len() - 1].split_whitespace().collect_vec();
+
+            let activation = inner[1].parse::<Activation>().unwrap();
+            let trigger = inner[2]
+                .parse::<Finger>()
+                .map_err(|e| format!("Invalid layer header {line}: {e}"))?;
+
+            let mut layer = Layer::new(inner[0], activation, trigger);
+            let (matrix, overflow) = Self::parse_grid(&lines[start + 1 .. end]);
+
+            layer.matrix = matrix;
+            extra.extend(overflow);
+
+            layers.push(layer);
+        }
+
+        return Ok(layers);
+    }
+
+    /// Writes a layout in the `.kb` format. The header block is only written when the layout has
+    /// metadata, so layouts without any stay readable as v1 files.
+    pub fn serialize_layout(layout: &Layout) -> String
+    {
+        let mut result = String::new();
+
+        if !layout.metadata.is_empty()
+        {
+            let header = toml::to_string(&layout.metadata).unwrap();
+
+            result.push_str(format!("{HEADER_DELIMITER}\n{header}{HEADER_DELIMITER}\n").as_str());
+        }
+
+        result.push_str(Self::format_grid(&layout.matrix).as_str());
+
+        for layer in layout.layers.iter()
+        {
+            result.push_str(
+                format!(
+                    "\n[{} {} {:?}]\n{}",
+                    layer.name,
+                    layer.activation,
+                    layer.trigger,
+                    Self::format_grid(&layer.matrix)
+                )
+                .as_str(),
+            );
+        }
+
+        return result;
+    }
+
+    fn format_grid(grid: &Fixed<char>) -> String
+    {
+        let mut result = String::new();
+
+        for row in grid.chunks(10)
+        {
+            let keys = row
+                .iter()
+                .map(|c| {
+                    if *c == EMPTY_KEY
+                    {
+                        return EMPTY_TOKEN.to_string();
+                    }
+
+                    return c.to_string();
+                })
+                .collect_vec();
+
+            result.push_str(format!("{}  {}\n", keys[.. 5].join(" "), keys[5 ..].join(" ")).as_str());
+        }
+
+        return result;
+    }
+
+    /// Writes a layout to `static/layouts/<language>/<name>.kb`. Names that would put it in
+    /// another folder are refused.
+    pub fn save_layout(language: &str, name: &str, layout: &Layout) -> std::io::Result<PathBuf>
+    {
+        if name.is_empty() || name.contains(['/', '\\']) || name.contains("..")
+        {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "a layout name can't be empty or contain '/', '\\' or '..'",
+            ));
+        }
+
+        let mut path_buf = PathBuf::from(ROOT);
 
-        return Layout::from(layout);
+        path_buf.push("layouts");
+        path_buf.push(language);
+        path_buf.push(format!("{name}.kb"));
+
+        std::fs::write(path_buf.as_path(), Self::serialize_layout(layout))?;
+
+        return Ok(path_buf);
+    }
+
+    /// Loads `static/corpus_configs/<name>.toml`, or a file with that name in one of its direct
+    /// subfolders, with everything it inherits merged in. There is no config if the file doesn't
+    /// exist, and an error if it or one it inherits isn't valid.
+    pub fn corpus_config(name: &str) -> Result<Option<CorpusConfig>, String>
+    {
+        return Self::corpus_config_inner(name, &mut Vec::new());
+    }
+
+    fn corpus_config_inner(
+        name: &str,
+        visited: &mut Vec<String>,
+    ) -> Result<Option<CorpusConfig>, String>
+    {
+        if visited.iter().any(|v| v == name)
+        {
+            return Ok(None);
+        }
+
+        visited.push(name.to_string());
+
+        let path = match Self::corpus_config_path(name)
+        {
+            | None => return Ok(None),
+            | Some(path) => path,
+        };
+
+        let string = std::fs::read_to_string(path.as_path())
+            .map_err(|e| format!("Couldn't read {}: {e}", path.display()))?;
+
+        let mut config: CorpusConfig = toml::from_str(string.as_str())
+            .map_err(|e| format!("Invalid corpus config '{name}': {e}"))?;
+
+        for parent in config.inherits.clone()
+        {
+            if let Some(p) = Self::corpus_config_inner(parent.as_str(), visited)?
+            {
+                config.inherit(&p);
+            }
+        }
+
+        return Ok(Some(config));
+    }
+
+    fn corpus_config_path(name: &str) -> Option<PathBuf>
+    {
+        let mut path_buf = PathBuf::from(ROOT);
+        path_buf.push("corpus_configs");
+
+        let direct = path_buf.join(format!("{name}.toml"));
+
+        if direct.exists()
+        {
+            return Some(direct);
+        }
+
+        return std::fs::read_dir(path_buf)
+            .ok()?
+            .flatten()
+            .map(|e| e.path().join(format!("{name}.toml")))
+            .find(|p| p.exists());
+    }
+
+    /// Loads the QMK and ZMK matrix templates of a keyboard type from `static/templates/matrix.toml`.
+    /// There are none if the file doesn't exist, and an error if it isn't valid.
+    pub fn matrix_templates(keyboard_type: KeyboardType) -> Result<Option<BoardTemplates>, String>
+    {
+        let file_path = format!("{}/templates/matrix.toml", ROOT);
+
+        let string = match std::fs::read_to_string(file_path)
+        {
+            | Err(_) => return Ok(None),
+            | Ok(s) => s,
+        };
+
+        let mut templates: HashMap<String, BoardTemplates> = toml::from_str(string.as_str())
+            .map_err(|e| format!("Invalid matrix templates: {e}"))?;
+
+        return Ok(templates.remove(keyboard_type.to_string().replace(' ', "_").as_str()));
+    }
+
+    /// Imports a layout from another format: `kle` for Keyboard Layout Editor JSON, `klc` for
+    /// Microsoft Keyboard Layout Creator files or `cmini` for cmini JSON.
+    pub fn import_layout(path: &str, format: &str) -> Result<ImportReport, String>
+    {
+        let bytes = std::fs::read(path).map_err(|e| format!("Couldn't read {path}: {e}"))?;
+
+        // MSKLC saves files as UTF-16.
+        let string = match bytes.as_slice()
+        {
+            | [0xFF, 0xFE, rest @ ..] =>
+            {
+                let units = rest
+                    .chunks_exact(2)
+                    .map(|b| u16::from_le_bytes([b[0], b[1]]))
+                    .collect_vec();
+
+                String::from_utf16_lossy(&units)
+            },
+            | _ => String::from_utf8_lossy(&bytes).to_string(),
+        };
+
+        return match format.to_lowercase().as_str()
+        {
+            | "kle" => Kle::import(string.as_str()),
+            | "klc" => Klc::import(string.as_str()),
+            | "cmini" => Cmini::import(string.as_str()),
+            | _ => Err(format!("\"{format}\" is not a supported format, use kle, klc or cmini.")),
+        };
     }
 
     pub fn language_data_file(language_name: &str) -> File
@@ -88,6 +430,26 @@ impl DataFetch
         return File::open(file_path).unwrap();
     }
 
+    pub fn language_data(language_name: &str) -> LanguageData
+    {
+        let mut contents = String::new();
+
+        Self::language_data_file(language_name)
+            .read_to_string(&mut contents)
+            .unwrap();
+
+        return serde_json::from_str(contents.as_str()).unwrap();
+    }
+
+    /// The language data as arrays indexed by the language's key set.
+    pub fn indexed_data(language_name: &str) -> IndexedData
+    {
+        let language_data = Self::language_data(language_name);
+        let key_set = Self::language_key_set(&language_data);
+
+        return IndexedData::new(language_data, key_set);
+    }
+
     pub fn chars_in_languages_default() -> HashMap<String, Fixed<char>>
     {
         let mut f = File::open("languages_default.cfg")
@@ -99,13 +461,40 @@ impl DataFetch
         return Self::parse_lines(&mut file_contents);
     }
 
+    /// The 30 characters a language's layouts should have, from `languages_default.cfg`. Languages
+    /// without an entry there use their 30 most frequent characters.
+    pub fn language_key_set(language_data: &LanguageData) -> Fixed<char>
+    {
+        if let Some(chars) = Self::chars_in_languages_default().remove(&language_data.language)
+        {
+            return chars;
+        }
+
+        let mut key_set = [EMPTY_KEY; 30];
+
+        language_data
+            .characters
+            .iter()
+            .filter(|(c, _)| !c.is_whitespace())
+            .sorted_by(|(_, f0), (_, f1)| f1.partial_cmp(f0).unwrap())
+            .take(30)
+            .enumerate()
+            .for_each(|(i, (c, _))| key_set[i] = *c);
+
+        return key_set;
+    }
+
     fn parse_lines(file_contents: &mut String) -> HashMap<String, Fixed<char>>
     {
         let mut parsed = HashMap::new();
 
         for line in file_contents.lines()
         {
-            let c = line.chars().next().unwrap();
+            let c = match line.chars().next()
+            {
+                | None => continue,
+                | Some(c) => c,
+            };
 
             if c.is_whitespace() || c == '#'
             {
@@ -115,7 +504,7 @@ impl DataFetch
             Self::insert_from_line(&mut parsed, line);
         }
 
-        return parsed.try_into().unwrap();
+        return parsed;
     }
 
     fn insert_from_line(hash_map: &mut HashMap<String, Fixed<char>>, line: &str)
@@ -137,12 +526,13 @@ impl DataFetch
             panic!("No specified language");
         }
 
-        let chars: Fixed<char> = split[1].trim().chars().collect_vec().try_into().unwrap();
+        let chars = split[1].trim().chars().collect_vec();
 
-        if chars.len() != 30
+        let chars: Fixed<char> = match chars.try_into()
         {
-            panic!("{} characters in {languages:?}, 30 required.", chars.len());
-        }
+            | Ok(c) => c,
+            | Err(c) => panic!("{} characters in {languages:?}, 30 required.", c.len()),
+        };
 
         for language in languages
         {
@@ -150,3 +540,56 @@ impl DataFetch
         }
     }
 }
+
+#[test]
+fn layout_round_trip()
+{
+    let kb = "---\n\
+        name = \"semimak\"\n\
+        author = \"Semi\"\n\
+        year = 2021\n\
+        ---\n\
+        f l h v z  q w u o y\n\
+        s r n t k  c d e a i\n\
+        x ' b m j  p g , . /\n\
+        \n\
+        [sym hold RT]\n\
+        __ __ - =  __ [ ] \\ __ __\n\
+        ; __ __ __ __  __ __ __ __ __\n\
+        __ __ __ __ __  __ __ __ __ __\n";
+
+    let layout = DataFetch::parse_layout(kb).unwrap();
+
+    assert_eq!(layout.metadata.author.as_deref(), Some("Semi"));
+    assert_eq!(layout.layers[0].matrix[10], ';');
+    assert_eq!(DataFetch::parse_layout(&DataFetch::serialize_layout(&layout)), Ok(layout));
+}
+
+#[test]
+fn layout_files()
+{
+    use crate::hand::finger::Finger::LT;
+    use crate::layout::layer::Activation::OneShot;
+
+    let grid = "f l h v z  q w u o y\ns r n t k  c d e a i\nx ' b m j  p g , . /\n";
+
+    assert!(DataFetch::parse_layout(format!("---\nname = 1\n---\n{grid}").as_str()).is_err());
+    assert!(DataFetch::parse_layout(format!("---\nname = \"x\"\n{grid}").as_str()).is_err());
+
+    let bad_finger = format!("{grid}\n[sym hold XX]\n__ __ - =  __ [ ] \\ __ __\n");
+
+    assert!(DataFetch::parse_layout(bad_finger.as_str()).unwrap_err().contains("XX"));
+
+    let oneshot = DataFetch::parse_layout(format!("{grid}\n[num oneshot LT]\n1 2 3").as_str());
+    let layer = &oneshot.unwrap().layers[0];
+
+    assert_eq!((layer.name.as_str(), layer.activation, layer.trigger), ("num", OneShot, LT));
+    assert_eq!(layer.matrix[.. 4], ['1', '2', '3', EMPTY_KEY]);
+
+    let layout = Layout::default();
+
+    for name in ["../qwerty", "a/b", "a\\b", ".."]
+    {
+        assert!(DataFetch::save_layout("english", name, &layout).is_err());
+    }
+}
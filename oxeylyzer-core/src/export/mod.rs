@@ -0,0 +1,4 @@
+pub mod qmk;
+pub mod template;
+pub mod xkb;
+pub mod zmk;
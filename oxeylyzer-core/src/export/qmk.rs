@@ -0,0 +1,173 @@
+use crate::export::template::{
+    Keymap,
+    MatrixTemplate,
+};
+use crate::layout::layer::Activation;
+use crate::layout::layer::Activation::{
+    Hold,
+    OneShot,
+};
+use crate::layout::layout::Layout;
+use crate::stats::layer_stats::BASE_LAYER;
+use itertools::Itertools;
+
+pub struct Qmk {}
+
+impl Qmk
+{
+    /// Creates the `keymaps` array of a QMK `keymap.c`, with one `LAYOUT` macro per layer.
+    pub fn keymap(name: &str, layout: &Layout, template: &MatrixTemplate) -> String
+    {
+        let mut result = format!(
+            "// {name}, exported by oxeylyzer.\n\
+            const uint16_t PROGMEM keymaps[][MATRIX_ROWS][MATRIX_COLS] = {{\n"
+        );
+
+        let layers = [None]
+            .into_iter()
+            .chain((0 .. layout.layers.len()).map(Some))
+            .collect_vec();
+
+        for (n, layer) in layers.iter().enumerate()
+        {
+            let layer_name = match layer
+            {
+                | None => BASE_LAYER,
+                | Some(l) => layout.layers[*l].name.as_str(),
+            };
+
+            let mut rows = Self::fill(template, layout, *layer);
+            let last = rows.len() - 1;
+
+            // Every key is followed by a comma, except the very last one.
+            for (i, row) in rows.iter_mut().enumerate()
+            {
+                let len = row.len();
+
+                row.iter_mut()
+                    .enumerate()
+                    .filter(|(j, _)| i != last || *j != len - 1)
+                    .for_each(|(_, k)| k.push(','));
+            }
+
+            result.push_str(
+                format!(
+                    "    // {layer_name}\n    [{n}] = {}(\n{}\n    ),\n",
+                    template.layout_macro,
+                    Self::align(&rows, " ", 8).join("\n")
+                )
+                .as_str(),
+            );
+        }
+
+        result.push_str("};\n");
+
+        return result;
+    }
+}
+
+impl Keymap for Qmk
+{
+    fn key(c: char) -> Option<String>
+    {
+        let key = match c
+        {
+            | ',' => "KC_COMM",
+            | '.' => "KC_DOT",
+            | '/' => "KC_SLSH",
+            | ';' => "KC_SCLN",
+            | '\'' => "KC_QUOT",
+            | '[' => "KC_LBRC",
+            | ']' => "KC_RBRC",
+            | '\\' => "KC_BSLS",
+            | '-' => "KC_MINS",
+            | '=' => "KC_EQL",
+            | '`' => "KC_GRV",
+            | '!' => "KC_EXLM",
+            | '@' => "KC_AT",
+            | '#' => "KC_HASH",
+            | '$' => "KC_DLR",
+            | '%' => "KC_PERC",
+            | '^' => "KC_CIRC",
+            | '&' => "KC_AMPR",
+            | '*' => "KC_ASTR",
+            | '(' => "KC_LPRN",
+            | ')' => "KC_RPRN",
+            | '_' => "KC_UNDS",
+            | '+' => "KC_PLUS",
+            | '{' => "KC_LCBR",
+            | '}' => "KC_RCBR",
+            | '|' => "KC_PIPE",
+            | ':' => "KC_COLN",
+            | '"' => "KC_DQUO",
+            | '<' => "KC_LABK",
+            | '>' => "KC_RABK",
+            | '?' => "KC_QUES",
+            | '~' => "KC_TILD",
+            | c if c.is_ascii_lowercase() || c.is_ascii_digit() =>
+            {
+                return Some(format!("KC_{}", c.to_ascii_uppercase()));
+            },
+            | c if c.is_ascii_uppercase() => return Some(format!("S(KC_{c})")),
+            // Needs `UNICODE_ENABLE = yes` in rules.mk.
+            | c => return Some(format!("UC(0x{:04X})", c as u32)),
+        };
+
+        return Some(key.to_string());
+    }
+
+    fn literal(key: &str) -> String
+    {
+        return key.to_string();
+    }
+
+    fn layer_key(layer: usize, activation: Activation, fallback: Option<&str>) -> String
+    {
+        return match (activation, fallback)
+        {
+            | (Hold, None) => format!("MO({layer})"),
+            | (Hold, Some(key)) => format!("LT({layer}, {key})"),
+            | (OneShot, _) => format!("OSL({layer})"),
+        };
+    }
+
+    fn none() -> String
+    {
+        return "KC_NO".to_string();
+    }
+
+    fn transparent() -> String
+    {
+        return "KC_TRNS".to_string();
+    }
+}
+
+#[test]
+fn keymap()
+{
+    use crate::data_dir::DataFetch;
+
+    let layout = DataFetch::parse_layout("a b é c\n\n[sym hold RT]\n! __ ü\n").unwrap();
+
+    let template = MatrixTemplate {
+        layout_macro: "LAYOUT".to_string(),
+        matrix: "KC_TAB 0 1 2\n__ 3 RT:KC_SPC\n".to_string(),
+    };
+
+    let expected = r#"// test, exported by oxeylyzer.
+const uint16_t PROGMEM keymaps[][MATRIX_ROWS][MATRIX_COLS] = {
+    // base
+    [0] = LAYOUT(
+        KC_TAB,       KC_A,         KC_B,         UC(0x00E9),
+        KC_NO,        KC_C,         LT(1, KC_SPC)
+    ),
+    // sym
+    [1] = LAYOUT(
+        KC_TRNS,    KC_EXLM,    KC_TRNS,    UC(0x00FC),
+        KC_TRNS,    KC_TRNS,    KC_TRNS
+    ),
+};
+"#;
+
+    assert_eq!(Qmk::keymap("test", &layout, &template), expected);
+}
@@ -0,0 +1,164 @@
+use crate::hand::finger::Finger;
+use crate::layout::layer::{
+    Activation,
+    EMPTY_KEY,
+};
+use crate::layout::layout::Layout;
+use itertools::Itertools;
+use serde::Deserialize;
+
+/// Describes the physical keys of a board for a keymap format, one line per row. Each
+/// whitespace-separated token in `matrix` is one key:
+/// * `0` to `29`: the key at that position of the layout.
+/// * `LT` or `RT`: a thumb key that becomes the layer key of the first layer triggered by that
+///   thumb. `LT:<key>` also gives it a key for when it isn't used for a layer.
+/// * `__`: an unused key.
+/// * Anything else is written as is, e.g. `KC_TAB` for QMK or `TAB` for ZMK.
+#[derive(Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct MatrixTemplate
+{
+    /// The name of the QMK `LAYOUT` macro. Unused for ZMK.
+    #[serde(rename = "macro")]
+    pub layout_macro: String,
+    pub matrix: String,
+}
+
+#[derive(Deserialize, Clone, Default)]
+pub struct BoardTemplates
+{
+    pub qmk: MatrixTemplate,
+    pub zmk: MatrixTemplate,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Slot
+{
+    Key(usize),
+    Thumb(Finger, Option<String>),
+    Empty,
+    Literal(String),
+}
+
+impl MatrixTemplate
+{
+    pub fn rows(&self) -> Vec<Vec<Slot>>
+    {
+        return self
+            .matrix
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .map(|l| l.split_whitespace().map(Self::slot).collect_vec())
+            .collect_vec();
+    }
+
+    fn slot(token: &str) -> Slot
+    {
+        if token == "__"
+        {
+            return Slot::Empty;
+        }
+
+        if let Ok(i) = token.parse::<usize>()
+        {
+            if i < 30
+            {
+                return Slot::Key(i);
+            }
+        }
+
+        let (thumb, fallback) = match token.split_once(':')
+        {
+            | None => (token, None),
+            | Some((t, f)) => (t, Some(f.to_string())),
+        };
+
+        return match thumb
+        {
+            | "LT" => Slot::Thumb(Finger::LT, fallback),
+            | "RT" => Slot::Thumb(Finger::RT, fallback),
+            | _ => Slot::Literal(token.to_string()),
+        };
+    }
+}
+
+/// The key names of a keymap format.
+pub trait Keymap
+{
+    /// The key that types a character, if the format has one.
+    fn key(c: char) -> Option<String>;
+    fn literal(key: &str) -> String;
+    fn layer_key(layer: usize, activation: Activation, fallback: Option<&str>) -> String;
+    fn none() -> String;
+    fn transparent() -> String;
+
+    /// Fills a template with the keys of the base layer, or of one of the extra layers. Keys
+    /// that aren't part of the 30 key grid only do something on the base layer.
+    fn fill(template: &MatrixTemplate, layout: &Layout, layer: Option<usize>) -> Vec<Vec<String>>
+    {
+        let unused = match layer
+        {
+            | None => Self::none(),
+            | Some(_) => Self::transparent(),
+        };
+
+        return template
+            .rows()
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|slot| {
+                        return match (slot, layer)
+                        {
+                            | (Slot::Key(i), _) =>
+                            {
+                                let c = match layer
+                                {
+                                    | None => layout.matrix[*i],
+                                    | Some(l) => layout.layers[l][*i],
+                                };
+
+                                match c
+                                {
+                                    | EMPTY_KEY => unused.clone(),
+                                    | c => Self::key(c).unwrap_or(unused.clone()),
+                                }
+                            },
+                            | (Slot::Thumb(finger, fallback), None) =>
+                            {
+                                let fallback = fallback.as_deref();
+
+                                match layout.layers.iter().position(|l| l.trigger == *finger)
+                                {
+                                    | Some(i) => Self::layer_key(
+                                        i + 1,
+                                        layout.layers[i].activation,
+                                        fallback,
+                                    ),
+                                    | None => fallback.map(Self::literal).unwrap_or(unused.clone()),
+                                }
+                            },
+                            | (Slot::Literal(key), None) => Self::literal(key),
+                            | _ => unused.clone(),
+                        };
+                    })
+                    .collect_vec()
+            })
+            .collect_vec();
+    }
+
+    /// Lines up the keys of each row in columns.
+    fn align(rows: &[Vec<String>], separator: &str, indent: usize) -> Vec<String>
+    {
+        let width = rows.iter().flatten().map(|k| k.len()).max().unwrap_or(0);
+
+        return rows
+            .iter()
+            .map(|row| {
+                let keys = row.iter().map(|k| format!("{k:width$}")).join(separator);
+
+                format!("{:indent$}{}", "", keys.trim_end())
+            })
+            .collect_vec();
+    }
+}
@@ -0,0 +1,230 @@
+use crate::layout::layer::EMPTY_KEY;
+use crate::layout::layout::Layout;
+use itertools::Itertools;
+use std::collections::HashMap;
+
+/// The xkb key names of the 30 positions, row by row.
+const KEY_NAMES: [&str; 3] = ["AD", "AC", "AB"];
+
+/// Layers with this name are used for the shifted level instead of the corpus shift pairs.
+pub const SHIFT_LAYER: &str = "shift";
+
+pub struct Xkb {}
+
+impl Xkb
+{
+    /// Creates an xkb `symbols` file for a layout. The base layer goes on levels 1 and 2, with
+    /// level 2 taken from a layer named `shift` if there is one, or `shift_pairs` otherwise. The
+    /// first other layer goes on levels 3 and 4, behind right alt.
+    pub fn symbols(name: &str, layout: &Layout, shift_pairs: &HashMap<char, char>) -> String
+    {
+        let shift_layer = layout.layer(SHIFT_LAYER);
+
+        let extra = layout
+            .layers
+            .iter()
+            .filter(|l| l.name != SHIFT_LAYER)
+            .collect_vec();
+
+        let mut result = format!(
+            "// {name}, exported by oxeylyzer.\n\
+            // Save this as ~/.config/xkb/symbols/{name} and load it with `setxkbmap {name}`.\n"
+        );
+
+        extra.iter().skip(1).for_each(|l| {
+            result.push_str(
+                format!("// The '{}' layer doesn't fit in xkb levels and was left out.\n", l.name)
+                    .as_str(),
+            )
+        });
+
+        result.push_str(
+            format!(
+                "\n\
+                default partial alphanumeric_keys\n\
+                xkb_symbols \"basic\" {{\n    \
+                    include \"us(basic)\"\n    \
+                    name[Group1] = \"{name}\";\n\n"
+            )
+            .as_str(),
+        );
+
+        for (i, c) in layout.matrix.iter().enumerate()
+        {
+            let shifted = match shift_layer
+            {
+                | Some(l) => l[i],
+                | None => Self::shifted(*c, shift_pairs),
+            };
+
+            let mut levels = vec![Self::keysym(*c), Self::keysym(shifted)];
+
+            if let Some(layer) = extra.first()
+            {
+                levels.push(Self::keysym(layer[i]));
+                levels.push(Self::keysym(Self::shifted(layer[i], shift_pairs)));
+            }
+
+            result.push_str(
+                format!(
+                    "    key <{}{:02}> {{ [ {} ] }};\n",
+                    KEY_NAMES[i / 10],
+                    i % 10 + 1,
+                    levels.join(", ")
+                )
+                .as_str(),
+            );
+        }
+
+        if !extra.is_empty()
+        {
+            result.push_str("\n    include \"level3(ralt_switch)\"\n");
+        }
+
+        result.push_str("};\n");
+
+        return result;
+    }
+
+    fn shifted(c: char, shift_pairs: &HashMap<char, char>) -> char
+    {
+        if c == EMPTY_KEY
+        {
+            return EMPTY_KEY;
+        }
+
+        if let Some(shifted) = shift_pairs.get(&c)
+        {
+            return *shifted;
+        }
+
+        let mut upper = c.to_uppercase();
+
+        return match (upper.next(), upper.next())
+        {
+            | (Some(u), None) => u,
+            | _ => c,
+        };
+    }
+
+    /// The xkb keysym name for a character.
+    pub fn keysym(c: char) -> String
+    {
+        let name = match c
+        {
+            | EMPTY_KEY => "NoSymbol",
+            | '!' => "exclam",
+            | '"' => "quotedbl",
+            | '#' => "numbersign",
+            | '$' => "dollar",
+            | '%' => "percent",
+            | '&' => "ampersand",
+            | '\'' => "apostrophe",
+            | '(' => "parenleft",
+            | ')' => "parenright",
+            | '*' => "asterisk",
+            | '+' => "plus",
+            | ',' => "comma",
+            | '-' => "minus",
+            | '.' => "period",
+            | '/' => "slash",
+            | ':' => "colon",
+            | ';' => "semicolon",
+            | '<' => "less",
+            | '=' => "equal",
+            | '>' => "greater",
+            | '?' => "question",
+            | '@' => "at",
+            | '[' => "bracketleft",
+            | '\\' => "backslash",
+            | ']' => "bracketright",
+            | '^' => "asciicircum",
+            | '_' => "underscore",
+            | '`' => "grave",
+            | '{' => "braceleft",
+            | '|' => "bar",
+            | '}' => "braceright",
+            | '~' => "asciitilde",
+            | c if c.is_ascii_alphanumeric() => return c.to_string(),
+            | c => return format!("U{:04X}", c as u32),
+        };
+
+        return name.to_string();
+    }
+}
+
+#[test]
+fn keysym()
+{
+    assert_eq!(Xkb::keysym('a'), "a");
+    assert_eq!(Xkb::keysym('7'), "7");
+    assert_eq!(Xkb::keysym('\''), "apostrophe");
+    assert_eq!(Xkb::keysym('\\'), "backslash");
+    assert_eq!(Xkb::keysym('é'), "U00E9");
+    assert_eq!(Xkb::keysym('€'), "U20AC");
+    assert_eq!(Xkb::keysym(EMPTY_KEY), "NoSymbol");
+}
+
+#[test]
+fn symbols()
+{
+    use crate::data_dir::DataFetch;
+
+    let layout = DataFetch::parse_layout(
+        "q w e r t y u i o p\n\
+        a s d f g h j k l ;\n\
+        z x c v b n m , . é\n\
+        \n\
+        [sym hold RT]\n\
+        ! @ # $ % ^ & * ( )\n\
+        1 2 3 4 5 6 7 8 9 0\n",
+    )
+    .unwrap();
+
+    let shift_pairs = HashMap::from([(';', ':'), (',', '<'), ('.', '>'), ('1', '!')]);
+
+    let expected = r#"// test, exported by oxeylyzer.
+// Save this as ~/.config/xkb/symbols/test and load it with `setxkbmap test`.
+
+default partial alphanumeric_keys
+xkb_symbols "basic" {
+    include "us(basic)"
+    name[Group1] = "test";
+
+    key <AD01> { [ q, Q, exclam, exclam ] };
+    key <AD02> { [ w, W, at, at ] };
+    key <AD03> { [ e, E, numbersign, numbersign ] };
+    key <AD04> { [ r, R, dollar, dollar ] };
+    key <AD05> { [ t, T, percent, percent ] };
+    key <AD06> { [ y, Y, asciicircum, asciicircum ] };
+    key <AD07> { [ u, U, ampersand, ampersand ] };
+    key <AD08> { [ i, I, asterisk, asterisk ] };
+    key <AD09> { [ o, O, parenleft, parenleft ] };
+    key <AD10> { [ p, P, parenright, parenright ] };
+    key <AC01> { [ a, A, 1, exclam ] };
+    key <AC02> { [ s, S, 2, 2 ] };
+    key <AC03> { [ d, D, 3, 3 ] };
+    key <AC04> { [ f, F, 4, 4 ] };
+    key <AC05> { [ g, G, 5, 5 ] };
+    key <AC06> { [ h, H, 6, 6 ] };
+    key <AC07> { [ j, J, 7, 7 ] };
+    key <AC08> { [ k, K, 8, 8 ] };
+    key <AC09> { [ l, L, 9, 9 ] };
+    key <AC10> { [ semicolon, colon, 0, 0 ] };
+    key <AB01> { [ z, Z, NoSymbol, NoSymbol ] };
+    key <AB02> { [ x, X, NoSymbol, NoSymbol ] };
+    key <AB03> { [ c, C, NoSymbol, NoSymbol ] };
+    key <AB04> { [ v, V, NoSymbol, NoSymbol ] };
+    key <AB05> { [ b, B, NoSymbol, NoSymbol ] };
+    key <AB06> { [ n, N, NoSymbol, NoSymbol ] };
+    key <AB07> { [ m, M, NoSymbol, NoSymbol ] };
+    key <AB08> { [ comma, less, NoSymbol, NoSymbol ] };
+    key <AB09> { [ period, greater, NoSymbol, NoSymbol ] };
+    key <AB10> { [ U00E9, U00C9, NoSymbol, NoSymbol ] };
+
+    include "level3(ralt_switch)"
+};
+"#;
+
+    assert_eq!(Xkb::symbols("test", &layout, &shift_pairs), expected);
+}
@@ -0,0 +1,224 @@
+use crate::export::template::{
+    Keymap,
+    MatrixTemplate,
+};
+use crate::layout::layer::{
+    Activation,
+    EMPTY_KEY,
+};
+use crate::layout::layer::Activation::{
+    Hold,
+    OneShot,
+};
+use crate::layout::layout::Layout;
+use crate::stats::layer_stats::BASE_LAYER;
+use itertools::Itertools;
+
+pub struct Zmk {}
+
+impl Zmk
+{
+    /// Creates the `keymap` node of a ZMK `.keymap` file, with one child node per layer.
+    pub fn keymap(name: &str, layout: &Layout, template: &MatrixTemplate) -> String
+    {
+        let mut result = format!(
+            "// {name}, exported by oxeylyzer.\n\
+            #include <behaviors.dtsi>\n\
+            #include <dt-bindings/zmk/keys.h>\n"
+        );
+
+        for (c, layer, i) in Self::unsupported(layout)
+        {
+            result.push_str(
+                format!("// '{c}' at {layer} {i} has no ZMK keycode and was left out.\n").as_str(),
+            );
+        }
+
+        result.push_str(
+            "\n\
+            / {\n    \
+                keymap {\n        \
+                    compatible = \"zmk,keymap\";\n",
+        );
+
+        let layers = [None]
+            .into_iter()
+            .chain((0 .. layout.layers.len()).map(Some))
+            .collect_vec();
+
+        for layer in layers
+        {
+            let layer_name = match layer
+            {
+                | None => BASE_LAYER,
+                | Some(l) => layout.layers[l].name.as_str(),
+            };
+
+            let node = layer_name
+                .chars()
+                .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' })
+                .collect::<String>();
+
+            let rows = Self::fill(template, layout, layer);
+
+            result.push_str(
+                format!(
+                    "\n        {node}_layer {{\n            \
+                        bindings = <\n{}\n            \
+                        >;\n        \
+                    }};\n",
+                    Self::align(&rows, " ", 16).join("\n")
+                )
+                .as_str(),
+            );
+        }
+
+        result.push_str("    };\n};\n");
+
+        return result;
+    }
+
+    /// The keys of a layout that ZMK has no keycode for, with the layer and position of each.
+    /// They are left empty in the keymap.
+    pub fn unsupported(layout: &Layout) -> Vec<(char, String, usize)>
+    {
+        let layers = [(BASE_LAYER, &layout.matrix)]
+            .into_iter()
+            .chain(layout.layers.iter().map(|l| (l.name.as_str(), &l.matrix)));
+
+        return layers
+            .flat_map(|(name, matrix)| {
+                matrix
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, c)| **c != EMPTY_KEY && Self::key(**c).is_none())
+                    .map(move |(i, c)| (*c, name.to_string(), i))
+            })
+            .collect_vec();
+    }
+}
+
+impl Keymap for Zmk
+{
+    fn key(c: char) -> Option<String>
+    {
+        let key = match c
+        {
+            | ',' => "COMMA",
+            | '.' => "DOT",
+            | '/' => "FSLH",
+            | ';' => "SEMI",
+            | '\'' => "SQT",
+            | '[' => "LBKT",
+            | ']' => "RBKT",
+            | '\\' => "BSLH",
+            | '-' => "MINUS",
+            | '=' => "EQUAL",
+            | '`' => "GRAVE",
+            | '!' => "EXCL",
+            | '@' => "AT",
+            | '#' => "HASH",
+            | '$' => "DOLLAR",
+            | '%' => "PRCNT",
+            | '^' => "CARET",
+            | '&' => "AMPS",
+            | '*' => "STAR",
+            | '(' => "LPAR",
+            | ')' => "RPAR",
+            | '_' => "UNDER",
+            | '+' => "PLUS",
+            | '{' => "LBRC",
+            | '}' => "RBRC",
+            | '|' => "PIPE",
+            | ':' => "COLON",
+            | '"' => "DQT",
+            | '<' => "LT",
+            | '>' => "GT",
+            | '?' => "QMARK",
+            | '~' => "TILDE",
+            | c if c.is_ascii_lowercase() => return Some(format!("&kp {}", c.to_ascii_uppercase())),
+            | c if c.is_ascii_uppercase() => return Some(format!("&kp LS({c})")),
+            | c if c.is_ascii_digit() => return Some(format!("&kp N{c}")),
+            // ZMK has no built-in way to type other characters.
+            | _ => return None,
+        };
+
+        return Some(format!("&kp {key}"));
+    }
+
+    fn literal(key: &str) -> String
+    {
+        if key.starts_with('&')
+        {
+            return key.to_string();
+        }
+
+        return format!("&kp {key}");
+    }
+
+    fn layer_key(layer: usize, activation: Activation, fallback: Option<&str>) -> String
+    {
+        return match (activation, fallback)
+        {
+            | (Hold, None) => format!("&mo {layer}"),
+            | (Hold, Some(key)) => format!("&lt {layer} {key}"),
+            | (OneShot, _) => format!("&sl {layer}"),
+        };
+    }
+
+    fn none() -> String
+    {
+        return "&none".to_string();
+    }
+
+    fn transparent() -> String
+    {
+        return "&trans".to_string();
+    }
+}
+
+#[test]
+fn keymap()
+{
+    use crate::data_dir::DataFetch;
+
+    let layout = DataFetch::parse_layout("a b é c\n\n[sym hold RT]\n! __ ü\n").unwrap();
+
+    let template = MatrixTemplate {
+        layout_macro: String::new(),
+        matrix: "TAB 0 1 2\n__ 3 RT:SPACE\n".to_string(),
+    };
+
+    let expected = r#"// test, exported by oxeylyzer.
+#include <behaviors.dtsi>
+#include <dt-bindings/zmk/keys.h>
+// 'é' at base 2 has no ZMK keycode and was left out.
+// 'ü' at sym 2 has no ZMK keycode and was left out.
+
+/ {
+    keymap {
+        compatible = "zmk,keymap";
+
+        base_layer {
+            bindings = <
+                &kp TAB     &kp A       &kp B       &none
+                &none       &kp C       &lt 1 SPACE
+            >;
+        };
+
+        sym_layer {
+            bindings = <
+                &trans   &kp EXCL &trans   &trans
+                &trans   &trans   &trans
+            >;
+        };
+    };
+};
+"#;
+
+    assert_eq!(Zmk::keymap("test", &layout, &template), expected);
+    assert_eq!(
+        Zmk::unsupported(&layout),
+        [('é', "base".to_string(), 2), ('ü', "sym".to_string(), 2)]
+    );
+}
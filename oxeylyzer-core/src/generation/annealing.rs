@@ -0,0 +1,108 @@
+use crate::indexed_data::IndexedData;
+use crate::utility::scored_layout::ScoredLayout;
+use crate::utility::scorer::Scorer;
+use nanorand::{
+    Rng,
+    WyRand,
+};
+
+/// How simulated annealing cools down. The temperature is in points of the total score: a swap
+/// that makes the score `t` worse is kept about a third of the time at temperature `t`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Annealing
+{
+    pub iterations: usize,
+    pub start_temperature: f32,
+    pub end_temperature: f32,
+}
+
+impl Default for Annealing
+{
+    fn default() -> Self
+    {
+        return Self {
+            iterations: 3000,
+            start_temperature: 2.,
+            end_temperature: 0.01,
+        };
+    }
+}
+
+impl Annealing
+{
+    /// Tries random swaps out of `swaps`, keeping the ones that make the layout better and,
+    /// with a chance that shrinks as it cools down, the ones that make it worse. Then it climbs
+    /// to the nearest layout no single swap improves.
+    pub fn run(
+        &self,
+        data: &IndexedData,
+        scorer: &Scorer,
+        layout: &mut ScoredLayout,
+        swaps: &[(usize, usize)],
+        rng: &mut WyRand,
+    )
+    {
+        if swaps.is_empty()
+        {
+            return;
+        }
+
+        let cooling = (self.end_temperature / self.start_temperature)
+            .powf((self.iterations.max(1) as f32).recip());
+
+        let mut temperature = self.start_temperature;
+        let mut score = layout.score.total();
+
+        for _ in 0 .. self.iterations
+        {
+            let (a, b) = swaps[rng.generate_range(0 .. swaps.len())];
+            let swapped = layout.swap(data, scorer, a, b);
+
+            if swapped >= score || rng.generate::<f32>() < ((swapped - score) / temperature).exp()
+            {
+                score = swapped;
+            }
+            else
+            {
+                layout.swap(data, scorer, a, b);
+            }
+
+            temperature *= cooling;
+        }
+
+        Self::climb(data, scorer, layout, swaps);
+    }
+
+    /// Keeps every swap that makes the layout better, until none does.
+    pub fn climb(
+        data: &IndexedData,
+        scorer: &Scorer,
+        layout: &mut ScoredLayout,
+        swaps: &[(usize, usize)],
+    )
+    {
+        let mut score = layout.score.total();
+        let mut improved = true;
+
+        while improved
+        {
+            improved = false;
+
+            for (a, b) in swaps.iter()
+            {
+                let swapped = layout.swap(data, scorer, *a, *b);
+
+                // Rounding can make a swap and its undo both look a tiny bit better.
+                if swapped > score + 1e-4
+                {
+                    score = swapped;
+                    improved = true;
+                }
+                else
+                {
+                    layout.swap(data, scorer, *a, *b);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,282 @@
+use crate::config::pins::Pins;
+use crate::generation::annealing::Annealing;
+use crate::indexed_data::IndexedData;
+use crate::layout::layout::Layout;
+use crate::type_def::Fixed;
+use crate::utility::scored_layout::ScoredLayout;
+use crate::utility::scorer::Scorer;
+use nanorand::{
+    Rng,
+    WyRand,
+};
+
+/// How two parents are combined into a child. Both keep the keys in some positions of the
+/// parents where they are, and keep pinned keys in place.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Crossover
+{
+    /// Partially mapped crossover: a random range of positions comes from the first parent, the
+    /// rest from the second, with the keys that would be there twice swapped for the ones the
+    /// range pushed out.
+    Pmx,
+    /// Cycle crossover: every key comes from the same position in one of the parents, taking
+    /// whole cycles of positions from each in turn.
+    Cycle,
+}
+
+/// A genetic algorithm: a population of layouts of which the better ones are combined into new
+/// layouts, generation after generation.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Genetic
+{
+    pub population: usize,
+    pub generations: usize,
+    /// How many of the best layouts go on to the next generation unchanged.
+    pub elite: usize,
+    /// How many layouts are picked at random to choose the best of as a parent.
+    pub tournament: usize,
+    /// The chance that a child gets a random swap.
+    pub mutation: f32,
+    pub crossover: Crossover,
+}
+
+impl Default for Genetic
+{
+    fn default() -> Self
+    {
+        return Self {
+            population: 40,
+            generations: 150,
+            elite: 4,
+            tournament: 3,
+            mutation: 0.5,
+            crossover: Crossover::Pmx,
+        };
+    }
+}
+
+/// A layout of the population, with the position in the base layout every key came from.
+#[derive(Clone)]
+struct Individual
+{
+    genes: Fixed<usize>,
+    scored: ScoredLayout,
+}
+
+impl Genetic
+{
+    /// Evolves a population of shuffles of the keys of `base` that aren't pinned, and returns
+    /// the best layout of the last generation, climbed to where no single swap improves it.
+    pub fn run(
+        &self,
+        data: &IndexedData,
+        scorer: &Scorer,
+        base: &Layout,
+        pins: &Pins,
+        rng: &mut WyRand,
+    ) -> ScoredLayout
+    {
+        let swaps = pins.swaps();
+        let free = pins.free();
+
+        let mut population = (0 .. self.population.max(1))
+            .map(|_| Self::individual(data, scorer, base, Self::shuffled(pins, rng)))
+            .collect::<Vec<_>>();
+
+        Self::sort(&mut population);
+
+        for _ in 0 .. self.generations
+        {
+            let mut next = population[.. self.elite.min(population.len())].to_vec();
+
+            while next.len() < population.len()
+            {
+                let p0 = self.select(&population, rng);
+                let p1 = self.select(&population, rng);
+
+                let genes = match self.crossover
+                {
+                    | Crossover::Pmx =>
+                    {
+                        let a = rng.generate_range(0 .. free.len().max(1));
+                        let b = rng.generate_range(0 .. free.len().max(1));
+
+                        Self::pmx(&p0.genes, &p1.genes, &free[a.min(b) .. a.max(b)])
+                    },
+                    | Crossover::Cycle => Self::cycle(&p0.genes, &p1.genes),
+                };
+
+                let mut child = Self::individual(data, scorer, base, genes);
+
+                if !swaps.is_empty() && rng.generate::<f32>() < self.mutation
+                {
+                    let (a, b) = swaps[rng.generate_range(0 .. swaps.len())];
+
+                    child.genes.swap(a, b);
+                    child.scored.swap(data, scorer, a, b);
+                }
+
+                next.push(child);
+            }
+
+            population = next;
+
+            Self::sort(&mut population);
+        }
+
+        let mut best = population.swap_remove(0).scored;
+
+        Annealing::climb(data, scorer, &mut best, &swaps);
+
+        return best;
+    }
+
+    /// The positions of `base` with the ones that aren't pinned shuffled.
+    pub(crate) fn shuffled(pins: &Pins, rng: &mut WyRand) -> Fixed<usize>
+    {
+        let free = pins.free();
+        let mut genes = std::array::from_fn(|i| i);
+
+        for i in (1 .. free.len()).rev()
+        {
+            genes.swap(free[i], free[rng.generate_range(0 ..= i)]);
+        }
+
+        return genes;
+    }
+
+    /// `base` with on every position the key of the position in `genes`.
+    pub(crate) fn layout(base: &Layout, genes: &Fixed<usize>) -> Layout
+    {
+        let mut layout = base.clone();
+        layout.matrix = genes.map(|g| base.matrix[g]);
+
+        return layout;
+    }
+
+    fn individual(
+        data: &IndexedData,
+        scorer: &Scorer,
+        base: &Layout,
+        genes: Fixed<usize>,
+    ) -> Individual
+    {
+        return Individual {
+            genes,
+            scored: ScoredLayout::new(data, scorer, Self::layout(base, &genes)),
+        };
+    }
+
+    fn sort(population: &mut [Individual])
+    {
+        population.sort_by(|i0, i1| i1.scored.score.total().total_cmp(&i0.scored.score.total()));
+    }
+
+    /// The best of `tournament` layouts picked at random.
+    fn select<'a>(&self, population: &'a [Individual], rng: &mut WyRand) -> &'a Individual
+    {
+        return (0 .. self.tournament.max(1))
+            .map(|_| &population[rng.generate_range(0 .. population.len())])
+            .max_by(|i0, i1| i0.scored.score.total().total_cmp(&i1.scored.score.total()))
+            .unwrap();
+    }
+
+    /// Partially mapped crossover, with the positions in `range` taken from `p0`.
+    pub fn pmx(p0: &Fixed<usize>, p1: &Fixed<usize>, range: &[usize]) -> Fixed<usize>
+    {
+        let mut child = *p1;
+        let mut from_p0 = [false; 30];
+
+        for &i in range
+        {
+            from_p0[i] = true;
+            child[i] = p0[i];
+        }
+
+        for i in (0 .. 30).filter(|i| !from_p0[*i])
+        {
+            // The gene of p1 is already in the range, so take the one of p1 that it pushed out
+            // instead, and so on until it's one that isn't.
+            while let Some(j) = range.iter().find(|j| p0[**j] == child[i])
+            {
+                child[i] = p1[*j];
+            }
+        }
+
+        return child;
+    }
+
+    /// Cycle crossover, starting with the cycle of the first position from `p0`.
+    pub fn cycle(p0: &Fixed<usize>, p1: &Fixed<usize>) -> Fixed<usize>
+    {
+        let mut child = *p1;
+        let mut visited = [false; 30];
+        let mut from_p0 = true;
+
+        for start in 0 .. 30
+        {
+            if visited[start]
+            {
+                continue;
+            }
+
+            let mut i = start;
+
+            while !visited[i]
+            {
+                visited[i] = true;
+
+                if from_p0
+                {
+                    child[i] = p0[i];
+                }
+
+                i = p0.iter().position(|g| *g == p1[i]).unwrap();
+            }
+
+            from_p0 = !from_p0;
+        }
+
+        return child;
+    }
+}
+
+#[test]
+fn crossovers_make_permutations()
+{
+    let mut rng = WyRand::new_seed(3);
+    let mut pins = Pins::default();
+
+    pins.toggle(4);
+    pins.toggle(17);
+
+    let free = pins.free();
+
+    for _ in 0 .. 100
+    {
+        let p0 = Genetic::shuffled(&pins, &mut rng);
+        let p1 = Genetic::shuffled(&pins, &mut rng);
+
+        let a = rng.generate_range(0 .. free.len());
+        let b = rng.generate_range(0 .. free.len());
+
+        let pmx = Genetic::pmx(&p0, &p1, &free[a.min(b) .. a.max(b)]);
+        let cycle = Genetic::cycle(&p0, &p1);
+
+        for child in [pmx, cycle]
+        {
+            let mut sorted = child;
+            sorted.sort();
+
+            assert_eq!(sorted, std::array::from_fn(|i| i));
+            assert_eq!((child[4], child[17]), (4, 17));
+        }
+
+        assert!((0 .. 30).all(|i| cycle[i] == p0[i] || cycle[i] == p1[i]));
+
+        for &i in &free[a.min(b) .. a.max(b)]
+        {
+            assert_eq!(pmx[i], p0[i]);
+        }
+    }
+}
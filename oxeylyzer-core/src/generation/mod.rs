@@ -0,0 +1,220 @@
+pub mod annealing;
+pub mod genetic;
+pub mod pareto;
+
+use crate::config::config::Config;
+use crate::config::pins::Pins;
+use crate::generation::annealing::Annealing;
+use crate::generation::genetic::Genetic;
+use crate::indexed_data::IndexedData;
+use crate::layout::layout::Layout;
+use crate::utility::scored_layout::ScoredLayout;
+use crate::utility::scorer::Scorer;
+use nanorand::{
+    Rng,
+    WyRand,
+};
+use rayon::iter::{
+    IntoParallelIterator,
+    ParallelIterator,
+};
+use std::collections::HashSet;
+use std::str::FromStr;
+
+/// How a layout is optimized.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Optimizer
+{
+    Annealing(Annealing),
+    Genetic(Genetic),
+}
+
+impl FromStr for Optimizer
+{
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err>
+    {
+        return match s.to_lowercase().as_str()
+        {
+            | "annealing" | "sa" => Ok(Optimizer::Annealing(Annealing::default())),
+            | "genetic" | "ga" => Ok(Optimizer::Genetic(Genetic::default())),
+            | _ => Err(format!("Unknown optimizer '{s}', use annealing or genetic.")),
+        };
+    }
+}
+
+/// Makes layouts for a language by optimizing random ones on their total score.
+pub struct Generator<'a>
+{
+    data: &'a IndexedData,
+    scorer: Scorer,
+}
+
+impl<'a> Generator<'a>
+{
+    pub fn new(data: &'a IndexedData, config: &Config) -> Self
+    {
+        return Self {
+            data,
+            scorer: Scorer::new(config),
+        };
+    }
+
+    /// A layout with the key set of the language, to generate from.
+    pub fn key_set_layout(&self) -> Layout
+    {
+        return Layout {
+            matrix: self.data.key_set,
+            ..Default::default()
+        };
+    }
+
+    /// `base` with the keys that aren't pinned shuffled.
+    pub fn shuffle(&self, base: &Layout, pins: &Pins, rng: &mut WyRand) -> Layout
+    {
+        let mut layout = base.clone();
+        let free = pins.free();
+
+        for i in (1 .. free.len()).rev()
+        {
+            let j = rng.generate_range(0 ..= i);
+
+            layout.matrix.swap(free[i], free[j]);
+        }
+
+        return layout;
+    }
+
+    /// Optimizes `amount` layouts with the keys of `base`, moving only the ones that aren't
+    /// pinned, in parallel. Annealing starts from a shuffle of them, a genetic algorithm from a
+    /// population of shuffles. `done` is called every time one is finished. Best first.
+    pub fn generate(
+        &self,
+        base: &Layout,
+        pins: &Pins,
+        amount: usize,
+        optimizer: &Optimizer,
+        done: impl Fn() + Sync,
+    ) -> Vec<ScoredLayout>
+    {
+        let swaps = pins.swaps();
+
+        let mut layouts = (0 .. amount)
+            .into_par_iter()
+            .map(|_| {
+                let mut rng = WyRand::new();
+
+                let scored = match optimizer
+                {
+                    | Optimizer::Annealing(annealing) =>
+                    {
+                        let layout = self.shuffle(base, pins, &mut rng);
+                        let mut scored = ScoredLayout::new(self.data, &self.scorer, layout);
+
+                        annealing.run(self.data, &self.scorer, &mut scored, &swaps, &mut rng);
+
+                        scored
+                    },
+                    | Optimizer::Genetic(genetic) =>
+                    {
+                        genetic.run(self.data, &self.scorer, base, pins, &mut rng)
+                    },
+                };
+
+                done();
+
+                return scored;
+            })
+            .collect::<Vec<_>>();
+
+        layouts.sort_by(|l0, l1| l1.score.total().total_cmp(&l0.score.total()));
+
+        return layouts;
+    }
+}
+
+/// The layouts that are different from `base` and every layout before them. The order is kept.
+/// Mirrored layouts are kept too, as they don't score the same on most keyboard types.
+pub fn distinct(base: &Layout, layouts: Vec<ScoredLayout>) -> Vec<ScoredLayout>
+{
+    let mut seen = HashSet::from([base.matrix]);
+
+    return layouts
+        .into_iter()
+        .filter(|l| seen.insert(l.layout.matrix))
+        .collect();
+}
+
+#[test]
+fn generation()
+{
+    use crate::language_data::LanguageData;
+    use itertools::Itertools;
+
+    let language_data = LanguageData::test()
+        .with_characters(&[('e', 0.3), ('t', 0.25), ('a', 0.2), ('o', 0.15), ('n', 0.1)])
+        .with_bigrams(&[("th", 0.3), ("he", 0.3), ("an", 0.2), ("on", 0.1), ("ea", 0.1)])
+        .with_skipgrams(1, &[("te", 0.5), ("ao", 0.5)])
+        .with_trigrams(&[("the", 0.5), ("and", 0.3), ("ion", 0.2)]);
+
+    let chars = "abcdefghijklmnopqrstuvwxyz',.;".chars().collect_vec();
+    let data = IndexedData::new(language_data, chars.clone().try_into().unwrap());
+    let generator = Generator::new(&data, &Config::default());
+
+    let base = generator.key_set_layout();
+    let mut pins = Pins::default();
+
+    pins.toggle(0);
+    pins.toggle(13);
+    pins.toggle(29);
+
+    let swaps = pins.swaps();
+    let mut rng = WyRand::new_seed(7);
+
+    let is_permutation = |layout: &Layout| {
+        let mut sorted = layout.matrix;
+        let mut expected = base.matrix;
+
+        sorted.sort();
+        expected.sort();
+
+        return sorted == expected;
+    };
+
+    let is_pinned =
+        |layout: &Layout| [0, 13, 29].iter().all(|i| layout.matrix[*i] == base.matrix[*i]);
+
+    for _ in 0 .. 2
+    {
+        let layout = generator.shuffle(&base, &pins, &mut rng);
+
+        assert!(is_permutation(&layout) && is_pinned(&layout));
+
+        let mut scored = ScoredLayout::new(&data, &generator.scorer, layout.clone());
+        let shuffled = scored.score.total();
+
+        Annealing::climb(&data, &generator.scorer, &mut scored, &swaps);
+
+        assert!(scored.score.total() >= shuffled);
+        assert!(is_permutation(&scored.layout) && is_pinned(&scored.layout));
+
+        let mut annealed = ScoredLayout::new(&data, &generator.scorer, layout);
+        let annealing = Annealing {
+            iterations: 200,
+            ..Default::default()
+        };
+
+        annealing.run(&data, &generator.scorer, &mut annealed, &swaps, &mut rng);
+
+        assert!(is_permutation(&annealed.layout) && is_pinned(&annealed.layout));
+    }
+
+    // A mirrored layout can score differently, so only exact repeats are left out.
+    let mirrored = ScoredLayout::new(&data, &generator.scorer, base.mirrored());
+    let same = ScoredLayout::new(&data, &generator.scorer, base.clone());
+    let kept = distinct(&base, vec![same, mirrored.clone(), mirrored]);
+
+    assert_eq!(kept.len(), 1);
+    assert_eq!(kept[0].layout.matrix, base.mirrored().matrix);
+}
@@ -0,0 +1,463 @@
+use crate::config::pins::Pins;
+use crate::generation::genetic::Genetic;
+use crate::indexed_data::IndexedData;
+use crate::layout::layout::Layout;
+use crate::type_def::Fixed;
+use crate::utility::scored_layout::ScoredLayout;
+use crate::utility::scorer::{
+    Score,
+    Scorer,
+};
+use nanorand::{
+    Rng,
+    WyRand,
+};
+use rayon::iter::{
+    IntoParallelIterator,
+    ParallelIterator,
+};
+use std::cmp::Ordering;
+
+/// A multi-objective search in the style of NSGA-II: instead of the total score, it optimizes
+/// several terms of the score at once, and finds the layouts that no other layout beats on every
+/// one of them.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pareto
+{
+    pub population: usize,
+    pub generations: usize,
+    /// The chance that a child gets a random swap.
+    pub mutation: f32,
+}
+
+impl Default for Pareto
+{
+    fn default() -> Self
+    {
+        return Self {
+            population: 60,
+            generations: 100,
+            mutation: 0.5,
+        };
+    }
+}
+
+#[derive(Clone)]
+struct Member
+{
+    genes: Fixed<usize>,
+    scored: ScoredLayout,
+    objectives: Vec<f32>,
+    rank: usize,
+    crowding: f32,
+}
+
+impl Pareto
+{
+    /// The names of the score terms that `metrics` stand for, case insensitive. Only terms with
+    /// a weight can be optimized, since the weight says whether more of it is better or worse.
+    pub fn objectives(score: &Score, metrics: &[String]) -> Result<Vec<String>, String>
+    {
+        return metrics
+            .iter()
+            .map(|metric| {
+                let (name, term) = score
+                    .terms
+                    .iter()
+                    .find(|(name, _)| name.eq_ignore_ascii_case(metric))
+                    .ok_or(format!("\"{metric}\" is not a term of the score."))?;
+
+                if term.weight == 0.
+                {
+                    return Err(format!("\"{name}\" has no weight in the config."));
+                }
+
+                return Ok(name.clone());
+            })
+            .collect();
+    }
+
+    /// The terms `objectives` of a score, weighted, so that higher is better for all of them.
+    pub fn of(score: &Score, objectives: &[String]) -> Vec<f32>
+    {
+        return objectives
+            .iter()
+            .map(|name| score.terms.get(name).map_or(0., |term| term.score()))
+            .collect();
+    }
+
+    /// Evolves a population of shuffles of the keys of `base` that aren't pinned on the score
+    /// terms `objectives`, and returns the layouts of the last generation that no other one
+    /// beats on all of them, ordered by the first objective. `done` is called after every
+    /// generation.
+    pub fn run(
+        &self,
+        data: &IndexedData,
+        scorer: &Scorer,
+        base: &Layout,
+        pins: &Pins,
+        objectives: &[String],
+        done: impl Fn(),
+    ) -> Vec<ScoredLayout>
+    {
+        return self.evolve(data, scorer, base, pins, objectives, done, &mut WyRand::new());
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn evolve(
+        &self,
+        data: &IndexedData,
+        scorer: &Scorer,
+        base: &Layout,
+        pins: &Pins,
+        objectives: &[String],
+        done: impl Fn(),
+        rng: &mut WyRand,
+    ) -> Vec<ScoredLayout>
+    {
+        let swaps = pins.swaps();
+        let free = pins.free();
+        let size = self.population.max(2);
+
+        let genes = (0 .. size)
+            .map(|_| Genetic::shuffled(pins, rng))
+            .collect::<Vec<_>>();
+
+        let mut population = Self::members(data, scorer, base, genes, objectives);
+
+        Self::rank(&mut population);
+
+        for _ in 0 .. self.generations
+        {
+            let children = (0 .. size)
+                .map(|_| {
+                    let p0 = Self::select(&population, rng);
+                    let p1 = Self::select(&population, rng);
+
+                    let a = rng.generate_range(0 .. free.len().max(1));
+                    let b = rng.generate_range(0 .. free.len().max(1));
+                    let range = &free[a.min(b) .. a.max(b)];
+
+                    let mut genes = Genetic::pmx(&p0.genes, &p1.genes, range);
+
+                    if !swaps.is_empty() && rng.generate::<f32>() < self.mutation
+                    {
+                        let (a, b) = swaps[rng.generate_range(0 .. swaps.len())];
+
+                        genes.swap(a, b);
+                    }
+
+                    return genes;
+                })
+                .collect::<Vec<_>>();
+
+            population.extend(Self::members(data, scorer, base, children, objectives));
+
+            Self::rank(&mut population);
+
+            population.sort_by(Self::compare);
+            population.truncate(size);
+
+            done();
+        }
+
+        let mut front = population.into_iter().filter(|m| m.rank == 0).collect::<Vec<_>>();
+
+        front.sort_by(|m0, m1| m1.objectives[0].total_cmp(&m0.objectives[0]));
+
+        return front.into_iter().map(|m| m.scored).collect();
+    }
+
+    fn members(
+        data: &IndexedData,
+        scorer: &Scorer,
+        base: &Layout,
+        genes: Vec<Fixed<usize>>,
+        objectives: &[String],
+    ) -> Vec<Member>
+    {
+        return genes
+            .into_par_iter()
+            .map(|genes| {
+                let scored = ScoredLayout::new(data, scorer, Genetic::layout(base, &genes));
+                let objectives = Self::of(&scored.score, objectives);
+
+                return Member {
+                    genes,
+                    scored,
+                    objectives,
+                    rank: 0,
+                    crowding: 0.,
+                };
+            })
+            .collect();
+    }
+
+    /// Whether `a` is at least as good as `b` on every objective, and better on one.
+    fn dominates(a: &[f32], b: &[f32]) -> bool
+    {
+        return a.iter().zip(b).all(|(a, b)| a >= b) && a.iter().zip(b).any(|(a, b)| a > b);
+    }
+
+    /// Sorts the population into fronts: the first one is the members no other member
+    /// dominates, the second the ones only the first front dominates, and so on. Within a front,
+    /// members that are far from the others on the objectives get a higher crowding distance.
+    fn rank(population: &mut [Member])
+    {
+        let n = population.len();
+
+        let mut dominated_by = vec![0; n];
+        let mut dominates = vec![Vec::new(); n];
+
+        for i in 0 .. n
+        {
+            for j in 0 .. n
+            {
+                if Self::dominates(&population[i].objectives, &population[j].objectives)
+                {
+                    dominates[i].push(j);
+                    dominated_by[j] += 1;
+                }
+            }
+        }
+
+        let mut front = (0 .. n).filter(|i| dominated_by[*i] == 0).collect::<Vec<_>>();
+        let mut rank = 0;
+
+        while !front.is_empty()
+        {
+            let mut next = Vec::new();
+
+            for &i in front.iter()
+            {
+                population[i].rank = rank;
+
+                for &j in dominates[i].iter()
+                {
+                    dominated_by[j] -= 1;
+
+                    if dominated_by[j] == 0
+                    {
+                        next.push(j);
+                    }
+                }
+            }
+
+            Self::crowding(population, &front);
+
+            front = next;
+            rank += 1;
+        }
+    }
+
+    /// For every member of a front, the sum over the objectives of how far apart its neighbours
+    /// on that objective are, relative to the range of the front. The ends are always kept.
+    fn crowding(population: &mut [Member], front: &[usize])
+    {
+        for &i in front
+        {
+            population[i].crowding = 0.;
+        }
+
+        for o in 0 .. population[front[0]].objectives.len()
+        {
+            let mut sorted = front.to_vec();
+
+            sorted.sort_by(|i, j| {
+                population[*i].objectives[o].total_cmp(&population[*j].objectives[o])
+            });
+
+            let (first, last) = (sorted[0], sorted[sorted.len() - 1]);
+            let range = population[last].objectives[o] - population[first].objectives[o];
+
+            population[first].crowding = f32::INFINITY;
+            population[last].crowding = f32::INFINITY;
+
+            if range <= 0.
+            {
+                continue;
+            }
+
+            for w in sorted.windows(3)
+            {
+                let distance = population[w[2]].objectives[o] - population[w[0]].objectives[o];
+
+                population[w[1]].crowding += distance / range;
+            }
+        }
+    }
+
+    /// Better fronts first, and within a front the less crowded members.
+    fn compare(m0: &Member, m1: &Member) -> Ordering
+    {
+        return m0
+            .rank
+            .cmp(&m1.rank)
+            .then(m1.crowding.total_cmp(&m0.crowding));
+    }
+
+    /// The better of two members picked at random.
+    fn select<'a>(population: &'a [Member], rng: &mut WyRand) -> &'a Member
+    {
+        let m0 = &population[rng.generate_range(0 .. population.len())];
+        let m1 = &population[rng.generate_range(0 .. population.len())];
+
+        return match Self::compare(m0, m1)
+        {
+            | Ordering::Greater => m1,
+            | _ => m0,
+        };
+    }
+}
+
+#[test]
+fn objectives()
+{
+    use crate::utility::scorer::ScoreTerm;
+
+    let mut score = Score::default();
+
+    score.terms.insert("SFB".to_string(), ScoreTerm { value: 1., weight: -2. });
+    score.terms.insert("IRB".to_string(), ScoreTerm { value: 3., weight: 0. });
+
+    let objectives = Pareto::objectives(&score, &["sfb".to_string()]).unwrap();
+
+    assert_eq!(objectives, ["SFB"]);
+    assert_eq!(Pareto::of(&score, &objectives), [-2.]);
+    assert!(Pareto::objectives(&score, &["IRB".to_string()]).is_err());
+    assert!(Pareto::objectives(&score, &["nope".to_string()]).is_err());
+
+    assert!(Pareto::dominates(&[1., 2.], &[1., 1.]));
+    assert!(!Pareto::dominates(&[1., 1.], &[1., 1.]));
+    assert!(!Pareto::dominates(&[2., 0.], &[1., 1.]));
+}
+
+#[cfg(test)]
+fn population(objectives: &[&[f32]]) -> Vec<Member>
+{
+    use crate::config::config::Config;
+    use crate::language_data::LanguageData;
+    use itertools::Itertools;
+
+    let chars: Fixed<char> = "abcdefghijklmnopqrstuvwxyz',.;"
+        .chars()
+        .collect_vec()
+        .try_into()
+        .unwrap();
+
+    let data = IndexedData::new(LanguageData::test(), chars);
+    let scored = ScoredLayout::new(&data, &Scorer::new(&Config::default()), chars.into());
+
+    return objectives
+        .iter()
+        .map(|o| Member {
+            genes: std::array::from_fn(|i| i),
+            scored: scored.clone(),
+            objectives: o.to_vec(),
+            rank: 0,
+            crowding: 0.,
+        })
+        .collect();
+}
+
+#[test]
+fn rank()
+{
+    let mut population =
+        population(&[&[3., 1.], &[1., 3.], &[2., 2.], &[1., 1.], &[0., 0.], &[2., 2.]]);
+
+    Pareto::rank(&mut population);
+
+    let ranks = population.iter().map(|m| m.rank).collect::<Vec<_>>();
+
+    // Equal members don't dominate each other.
+    assert_eq!(ranks, [0, 0, 0, 1, 2, 0]);
+
+    // The ends of the first front, and the only member of the others.
+    for i in [0, 1, 3, 4]
+    {
+        assert_eq!(population[i].crowding, f32::INFINITY);
+    }
+
+    // Half the range on each objective.
+    assert_eq!(population[2].crowding, 1.);
+    assert_eq!(population[5].crowding, 1.);
+
+    population.sort_by(Pareto::compare);
+
+    let ranks = population.iter().map(|m| m.rank).collect::<Vec<_>>();
+
+    assert_eq!(ranks, [0, 0, 0, 0, 1, 2]);
+    assert_eq!(population[3].crowding, 1.);
+}
+
+#[test]
+fn crowding()
+{
+    // Two members are both ends.
+    let mut pair = population(&[&[1., 0.], &[0., 1.]]);
+
+    Pareto::rank(&mut pair);
+
+    assert!(pair.iter().all(|m| m.rank == 0 && m.crowding == f32::INFINITY));
+
+    // The first objective has no range, so only the others count.
+    let mut flat = population(&[&[1., 2., 0.], &[1., 1., 1.], &[1., 0., 2.]]);
+
+    Pareto::rank(&mut flat);
+
+    assert_eq!(flat[0].crowding, f32::INFINITY);
+    assert_eq!(flat[1].crowding, 2.);
+    assert_eq!(flat[2].crowding, f32::INFINITY);
+}
+
+#[test]
+fn run()
+{
+    use crate::config::config::Config;
+    use crate::language_data::LanguageData;
+    use itertools::Itertools;
+
+    let language_data = LanguageData::test()
+        .with_characters(&[('e', 0.3), ('t', 0.25), ('a', 0.2), ('o', 0.15), ('n', 0.1)])
+        .with_bigrams(&[("th", 0.3), ("he", 0.3), ("an", 0.2), ("on", 0.1), ("ea", 0.1)])
+        .with_trigrams(&[("the", 0.5), ("and", 0.3), ("ion", 0.2)]);
+
+    let chars: Fixed<char> = "abcdefghijklmnopqrstuvwxyz',.;"
+        .chars()
+        .collect_vec()
+        .try_into()
+        .unwrap();
+
+    let data = IndexedData::new(language_data, chars);
+    let scorer = Scorer::new(&Config::default());
+    let base = Layout::from(chars);
+    let objectives = ["SFB".to_string(), "IRT".to_string()];
+
+    let pareto = Pareto {
+        population: 8,
+        generations: 5,
+        ..Default::default()
+    };
+
+    let front = pareto.evolve(
+        &data,
+        &scorer,
+        &base,
+        &Pins::default(),
+        &objectives,
+        || {},
+        &mut WyRand::new_seed(11),
+    );
+
+    assert!(!front.is_empty());
+
+    let values = front.iter().map(|s| Pareto::of(&s.score, &objectives)).collect_vec();
+
+    for (v0, v1) in values.iter().cartesian_product(values.iter())
+    {
+        assert!(!Pareto::dominates(v0, v1), "{v0:?} dominates {v1:?}");
+    }
+
+    assert!(values.windows(2).all(|w| w[0][0] >= w[1][0]));
+}
@@ -15,9 +15,10 @@ use crate::{
     hand::hand::Hand::*,
 };
 use num_derive::FromPrimitive;
+use std::str::FromStr;
 
 #[repr(u8)]
-#[derive(FromPrimitive, Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(FromPrimitive, Copy, Clone, Debug, Eq, PartialEq, Hash)]
 pub enum Finger {
     LP = 0,
     LR = 1,
@@ -49,6 +50,26 @@ impl From<u8> for Finger {
     }
 }
 
+impl FromStr for Finger {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        return match s.to_uppercase().as_str() {
+            | "LP" => Ok(LP),
+            | "LR" => Ok(LR),
+            | "LM" => Ok(LM),
+            | "LI" => Ok(LI),
+            | "LT" => Ok(LT),
+            | "RT" => Ok(RT),
+            | "RI" => Ok(RI),
+            | "RM" => Ok(RM),
+            | "RR" => Ok(RR),
+            | "RP" => Ok(RP),
+            | _ => Err(format!("'{s}' is not a finger, use one of LP LR LM LI LT RT RI RM RR RP.")),
+        };
+    }
+}
+
 impl Finger {
     pub const fn eq(self, other: Self) -> bool {
         return self as u8 == other as u8;
@@ -3,7 +3,7 @@ use Hand::*;
 use crate::hand::finger::Finger;
 
 #[repr(u8)]
-#[derive(Eq, Copy, Clone, Debug, PartialEq)]
+#[derive(Eq, Copy, Clone, Debug, PartialEq, Hash)]
 pub enum Hand {
     Left,
     Right,
@@ -0,0 +1,146 @@
+use crate::config::finger_to_column::FingerToColumn;
+use crate::hand::finger::Finger;
+use crate::import::report::ImportReport;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+#[derive(Deserialize)]
+struct CminiKey
+{
+    row: usize,
+    col: usize,
+    finger: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct CminiLayout
+{
+    name: Option<String>,
+    author: Option<String>,
+    board: Option<String>,
+    keys: BTreeMap<String, CminiKey>,
+}
+
+pub struct Cmini {}
+
+impl Cmini
+{
+    /// Imports a layout in the JSON format of the cmini bot, where every key has a `row` and `col`.
+    /// Rows 0 to 2 and columns 0 to 9 make up the grid, so thumb keys and keys like `[` end up
+    /// being reported. So are keys with a `finger` other than the default one of their column,
+    /// except on the bottom row of an angle mod board.
+    pub fn import(string: &str) -> Result<ImportReport, String>
+    {
+        let cmini: CminiLayout = serde_json::from_str(string).map_err(|e| e.to_string())?;
+
+        let mut report = ImportReport::default();
+
+        report.layout.metadata.name = cmini.name;
+        report.layout.metadata.author = cmini.author;
+        report.layout.metadata.board = cmini.board.clone().map(|b| {
+            match b.as_str()
+            {
+                | "stagger" => "rowstag".to_string(),
+                | "angle" => "ansi angle".to_string(),
+                | _ => b,
+            }
+        });
+
+        let angle = cmini.board.as_deref() == Some("angle");
+        let fingers = FingerToColumn::default();
+
+        let mut keys = cmini.keys.into_iter().collect::<Vec<_>>();
+        keys.sort_by_key(|(_, k)| (k.row, k.col));
+
+        for (key, position) in keys
+        {
+            let source = format!("row {}, column {}", position.row, position.col);
+
+            let mut chars = key.chars();
+
+            match (chars.next(), chars.next())
+            {
+                | _ if position.row >= 3 || position.col >= 10 =>
+                {
+                    report.unplace(key.as_str(), source.as_str());
+                },
+                | (Some(c), None) =>
+                {
+                    let i = position.row * 10 + position.col;
+
+                    let finger = position
+                        .finger
+                        .as_deref()
+                        .map(|f| f.parse::<Finger>().map_err(|_| f));
+
+                    match finger
+                    {
+                        | Some(Ok(f)) if f != fingers[i] && !(angle && position.row == 2) =>
+                        {
+                            let source = format!("{source}, typed with the {f}");
+
+                            report.unplace(key.as_str(), source.as_str());
+                        },
+                        | Some(Err(f)) =>
+                        {
+                            let source = format!("{source}, typed with unknown finger '{f}'");
+
+                            report.unplace(key.as_str(), source.as_str());
+                        },
+                        | _ => report.place(i, c, source.as_str()),
+                    }
+                },
+                | _ => report.unplace(key.as_str(), source.as_str()),
+            }
+        }
+
+        return Ok(report);
+    }
+}
+
+#[test]
+fn import()
+{
+    let report = Cmini::import(
+        r#"{
+            "name": "test",
+            "author": "someone",
+            "board": "angle",
+            "keys": {
+                "q": {"row": 0, "col": 0, "finger": "LP"},
+                "e": {"row": 0, "col": 2, "finger": "LM"},
+                "a": {"row": 1, "col": 0, "finger": "LR"},
+                "z": {"row": 2, "col": 0, "finger": "LR"},
+                "x": {"row": 1, "col": 9, "finger": "RR"},
+                "-": {"row": 0, "col": 10, "finger": "RP"},
+                "_": {"row": 3, "col": 0, "finger": "LT"},
+                "ch": {"row": 1, "col": 1, "finger": "LR"}
+            }
+        }"#,
+    )
+    .unwrap();
+
+    assert_eq!(report.layout.metadata.name.as_deref(), Some("test"));
+    assert_eq!(report.layout.metadata.board.as_deref(), Some("ansi angle"));
+    assert_eq!(report.layout.matrix[0], 'q');
+    assert_eq!(report.layout.matrix[2], 'e');
+    assert_eq!(report.layout.matrix[20], 'z');
+    assert_eq!(report.empty_positions().len(), 27);
+
+    let unplaced = report
+        .unplaced
+        .iter()
+        .map(|(k, s)| (k.as_str(), s.as_str()))
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        unplaced,
+        [
+            ("-", "row 0, column 10"),
+            ("a", "row 1, column 0, typed with the left ring"),
+            ("ch", "row 1, column 1"),
+            ("x", "row 1, column 9, typed with the right ring"),
+            ("_", "row 3, column 0"),
+        ]
+    );
+}
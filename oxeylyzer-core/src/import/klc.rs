@@ -0,0 +1,174 @@
+use crate::import::report::ImportReport;
+use itertools::Itertools;
+
+/// The first scan code of each row of the grid.
+const ROW_SCAN_CODES: [u32; 3] = [0x10, 0x1e, 0x2c];
+
+/// The sections that can follow `LAYOUT` in a `.klc` file.
+const SECTIONS: [&str; 7] = [
+    "LIGATURE",
+    "DEADKEY",
+    "KEYNAME",
+    "KEYNAME_EXT",
+    "KEYNAME_DEAD",
+    "DESCRIPTIONS",
+    "ENDKBD",
+];
+
+pub struct Klc {}
+
+impl Klc
+{
+    /// Imports a Microsoft Keyboard Layout Creator source file. Keys are placed by their scan
+    /// codes, so the letter rows of the keyboard become the grid.
+    pub fn import(string: &str) -> Result<ImportReport, String>
+    {
+        let mut report = ImportReport::default();
+
+        let lines = string
+            .lines()
+            .map(|l| l.split("//").next().unwrap().trim())
+            .collect_vec();
+
+        for line in lines.iter()
+        {
+            let tokens = line.split_whitespace().collect_vec();
+
+            match tokens.first()
+            {
+                | Some(&"KBD") =>
+                {
+                    report.layout.metadata.name = Self::quoted(line);
+                },
+                | Some(&"COMPANY") | Some(&"COPYRIGHT") if report.layout.metadata.author.is_none() =>
+                {
+                    report.layout.metadata.author = Self::quoted(line);
+                },
+                | _ => {},
+            }
+        }
+
+        let start = lines
+            .iter()
+            .position(|l| l.starts_with("LAYOUT"))
+            .ok_or("There is no LAYOUT section.")?;
+
+        for line in lines[start + 1 ..].iter()
+        {
+            let tokens = line.split_whitespace().collect_vec();
+
+            if tokens.first().is_some_and(|t| SECTIONS.contains(t))
+            {
+                break;
+            }
+
+            if tokens.len() < 4
+            {
+                continue;
+            }
+
+            let scan_code = match u32::from_str_radix(tokens[0], 16)
+            {
+                | Ok(s) => s,
+                | Err(_) => continue,
+            };
+
+            let c = match Self::character(tokens[3])
+            {
+                | Some(c) => c,
+                | None => continue,
+            };
+
+            let source = format!("scan code {}", tokens[0]);
+
+            match ROW_SCAN_CODES
+                .iter()
+                .position(|r| scan_code >= *r && scan_code < r + 10)
+            {
+                | Some(row) =>
+                {
+                    let position = row * 10 + (scan_code - ROW_SCAN_CODES[row]) as usize;
+
+                    report.place(position, c, source.as_str());
+                },
+                | None => report.unplace(c.to_string().as_str(), source.as_str()),
+            }
+        }
+
+        return Ok(report);
+    }
+
+    /// Reads a character column, which is either the character itself or its code point in hex.
+    /// `-1` means the key types nothing, and `%%` that it types more than one character.
+    fn character(token: &str) -> Option<char>
+    {
+        let token = token.trim_end_matches('@');
+
+        if token == "-1" || token == "%%"
+        {
+            return None;
+        }
+
+        let mut chars = token.chars();
+
+        if let (Some(c), None) = (chars.next(), chars.next())
+        {
+            return Some(c);
+        }
+
+        return u32::from_str_radix(token, 16).ok().and_then(char::from_u32);
+    }
+
+    fn quoted(line: &str) -> Option<String>
+    {
+        let start = line.find('"')?;
+        let end = line.rfind('"')?;
+
+        if end <= start
+        {
+            return None;
+        }
+
+        return Some(line[start + 1 .. end].to_string());
+    }
+}
+
+#[test]
+fn import()
+{
+    let report = Klc::import(
+        "KBD\ttest\t\"Test layout\"\n\
+        COPYRIGHT\t\"someone\"\n\
+        LAYOUT\t\t;an extra '@' at the end is a dead key\n\
+        //SC\tVK_\t\tCap\t0\t1\n\
+        02\t1\t\t0\t1\t0021\n\
+        10\tQ\t\t1\tq\tQ\n\
+        11\tW\t\t1\t00e9\t00c9\n\
+        1e\tA\t\t1\ta\tA\n\
+        1f\tS\t\t1\t-1\t-1\n\
+        2c\tZ\t\t1\tz\tZ\n\
+        35\tOEM_2\t\t0\t/\t?\n\
+        39\tSPACE\t\t0\t0020\t0020\n\
+        \n\
+        DEADKEY\t00b4\n\
+        1e\t00e1\n",
+    )
+    .unwrap();
+
+    assert_eq!(report.layout.metadata.name.as_deref(), Some("Test layout"));
+    assert_eq!(report.layout.metadata.author.as_deref(), Some("someone"));
+    assert_eq!(report.layout.matrix[0], 'q');
+    assert_eq!(report.layout.matrix[1], 'é');
+    assert_eq!(report.layout.matrix[10], 'a');
+    assert_eq!(report.layout.matrix[20], 'z');
+    assert_eq!(report.layout.matrix[29], '/');
+    assert_eq!(report.empty_positions().len(), 25);
+
+    let unplaced = report
+        .unplaced
+        .iter()
+        .map(|(k, s)| (k.as_str(), s.as_str()))
+        .collect::<Vec<_>>();
+
+    assert_eq!(unplaced, [("1", "scan code 02"), (" ", "scan code 39")]);
+}
@@ -0,0 +1,194 @@
+use crate::import::report::ImportReport;
+use itertools::Itertools;
+use serde_json::Value;
+
+/// A key with a single character legend, where its left edge is, and how far below the first key
+/// of its row it is.
+struct KleKey
+{
+    c: char,
+    x: f32,
+    y: f32,
+}
+
+pub struct Kle {}
+
+impl Kle
+{
+    /// Imports the raw data of a Keyboard Layout Editor layout. The last three rows with at least
+    /// 10 character keys are taken as the grid, where the top row starts at its first character
+    /// key and the rows below at the first one that isn't staggered to the left of it. Keys moved
+    /// half a key or more up or down from the first key of their row aren't part of that row.
+    pub fn import(string: &str) -> Result<ImportReport, String>
+    {
+        let json: Value = serde_json::from_str(string).map_err(|e| e.to_string())?;
+
+        let items = json
+            .as_array()
+            .ok_or("The layout should be an array of rows.")?;
+
+        let mut report = ImportReport::default();
+
+        if let Some(meta) = items.first().and_then(Value::as_object)
+        {
+            report.layout.metadata.name = meta.get("name").and_then(Value::as_str).map(str::to_string);
+            report.layout.metadata.author = meta.get("author").and_then(Value::as_str).map(str::to_string);
+        }
+
+        let mut rows = items
+            .iter()
+            .filter_map(Value::as_array)
+            .map(|row| Self::keys(row))
+            .collect_vec();
+
+        for (r, row) in rows.iter_mut().enumerate()
+        {
+            for key in row.extract_if(.., |k| k.y.abs() >= 0.5)
+            {
+                let offset = match key.y > 0.
+                {
+                    | true => format!("moved {} keys down", key.y),
+                    | false => format!("moved {} keys up", -key.y),
+                };
+
+                report.unplace(key.c.to_string().as_str(), format!("row {r}, {offset}").as_str());
+            }
+        }
+
+        let grid_rows = rows.iter().positions(|r| r.len() >= 10).collect_vec();
+
+        if grid_rows.len() < 3
+        {
+            return Err("Couldn't find three rows of at least 10 keys.".to_string());
+        }
+
+        let grid_rows = &grid_rows[grid_rows.len() - 3 ..];
+        let top_x = rows[grid_rows[0]][0].x;
+
+        for (r, row) in rows.iter().enumerate()
+        {
+            let start = match grid_rows.iter().position(|g| *g == r)
+            {
+                | None => None,
+                | Some(0) => Some(0),
+                | Some(_) => row
+                    .iter()
+                    .take(row.len() - 9)
+                    .position(|k| k.x >= top_x - 0.1),
+            };
+
+            for (i, key) in row.iter().enumerate()
+            {
+                let source = format!("row {r}, key {i}");
+
+                match (start, grid_rows.iter().position(|g| *g == r))
+                {
+                    | (Some(s), Some(g)) if i >= s && i < s + 10 =>
+                    {
+                        report.place(g * 10 + i - s, key.c, source.as_str());
+                    },
+                    | _ => report.unplace(key.c.to_string().as_str(), source.as_str()),
+                }
+            }
+        }
+
+        return Ok(report);
+    }
+
+    /// The character keys of a row, sorted by position. Keys with longer legends like `Shift` are
+    /// left out.
+    fn keys(row: &[Value]) -> Vec<KleKey>
+    {
+        let mut keys = Vec::new();
+
+        let mut x = 0.;
+        let mut y = 0.;
+        let mut width = 1.;
+
+        for item in row
+        {
+            match item
+            {
+                | Value::Object(props) =>
+                {
+                    x += props.get("x").and_then(Value::as_f64).unwrap_or(0.) as f32;
+                    y += props.get("y").and_then(Value::as_f64).unwrap_or(0.) as f32;
+                    width = props.get("w").and_then(Value::as_f64).unwrap_or(1.) as f32;
+                },
+                | Value::String(legend) =>
+                {
+                    if let Some(c) = Self::base_char(legend)
+                    {
+                        keys.push(KleKey { c, x, y });
+                    }
+
+                    x += width;
+                    width = 1.;
+                },
+                | _ => {},
+            }
+        }
+
+        let first = keys.first().map_or(0., |k| k.y);
+
+        keys.iter_mut().for_each(|k| k.y -= first);
+        keys.sort_by(|a, b| a.x.total_cmp(&b.x));
+
+        return keys;
+    }
+
+    /// The unshifted character of a legend, which is the bottom one if it has two, e.g. `!\n1`.
+    fn base_char(legend: &str) -> Option<char>
+    {
+        let legends = legend.split('\n').collect_vec();
+
+        let base = match legends.get(1)
+        {
+            | Some(l) if !l.is_empty() => l,
+            | _ => legends[0],
+        };
+
+        let mut chars = base.chars();
+
+        return match (chars.next(), chars.next())
+        {
+            | (Some(c), None) if !c.is_whitespace() => c.to_lowercase().next(),
+            | _ => None,
+        };
+    }
+}
+
+#[test]
+fn import()
+{
+    let report = Kle::import(
+        r##"[
+            {"name": "test", "author": "someone"},
+            ["~\n`", "!\n1", "@\n2", "#\n3", "$\n4", "%\n5", "^\n6", "&\n7", "*\n8", "(\n9",
+                ")\n0"],
+            [{"w": 1.5}, "Tab", "Q", "W", "E", "R", "T", "Y", "U", "I", "O", "P", "{\n["],
+            [{"w": 1.75}, "Caps", "A", "S", "D", "F", "G", "H", "J", "K", "L", ":\n;"],
+            [{"w": 2.25}, "Shift", "Z", {"y": 0.5}, "|\n\\", {"y": -0.5, "x": -1}, "X", "C", "V",
+                "B", "N", "M", "<\n,", ">\n.", "?\n/"],
+            [{"x": 11.5, "y": 0.25}, "↑"],
+            [{"y": -0.75, "x": 10.5}, "←", "↓", "→"]
+        ]"##,
+    )
+    .unwrap();
+
+    assert_eq!(report.layout.metadata.name.as_deref(), Some("test"));
+    assert_eq!(report.layout.matrix.iter().collect::<String>(), "qwertyuiopasdfghjkl;zxcvbnm,./");
+    assert!(report.empty_positions().is_empty());
+
+    let unplaced = report
+        .unplaced
+        .iter()
+        .map(|(k, s)| (k.as_str(), s.as_str()))
+        .collect::<Vec<_>>();
+
+    assert_eq!(unplaced[0], ("\\", "row 3, moved 0.5 keys down"));
+    assert_eq!(unplaced[1 .. 3], [("`", "row 0, key 0"), ("1", "row 0, key 1")]);
+    assert_eq!(unplaced[12], ("[", "row 1, key 10"));
+    assert_eq!(unplaced[13], ("↑", "row 4, key 0"));
+    assert_eq!(unplaced.len(), 17);
+}
@@ -0,0 +1,4 @@
+pub mod cmini;
+pub mod klc;
+pub mod kle;
+pub mod report;
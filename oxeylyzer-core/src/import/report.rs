@@ -0,0 +1,92 @@
+use crate::layout::layer::EMPTY_KEY;
+use crate::layout::layout::Layout;
+use itertools::Itertools;
+use std::fmt;
+use std::fmt::{
+    Display,
+    Formatter,
+};
+
+/// An imported layout, with the keys that couldn't be put in the 30 key grid.
+#[derive(Debug, Clone)]
+pub struct ImportReport
+{
+    pub layout: Layout,
+    /// Each key that was left out, with where it was found in the imported file.
+    pub unplaced: Vec<(String, String)>,
+}
+
+impl Default for ImportReport
+{
+    fn default() -> Self
+    {
+        return Self {
+            layout: Layout::from([EMPTY_KEY; 30]),
+            unplaced: Vec::new(),
+        };
+    }
+}
+
+impl ImportReport
+{
+    /// Puts a key at a position of the grid, unless that position is already taken.
+    pub fn place(&mut self, position: usize, c: char, source: &str)
+    {
+        if position >= 30
+        {
+            self.unplace(c.to_string().as_str(), source);
+        }
+        else if self.layout.matrix[position] != EMPTY_KEY
+        {
+            self.unplace(
+                c.to_string().as_str(),
+                format!("{source}, position {position} is already taken").as_str(),
+            );
+        }
+        else
+        {
+            self.layout.matrix[position] = c;
+        }
+    }
+
+    pub fn unplace(&mut self, key: &str, source: &str)
+    {
+        self.unplaced.push((key.to_string(), source.to_string()));
+    }
+
+    pub fn empty_positions(&self) -> Vec<usize>
+    {
+        return self
+            .layout
+            .matrix
+            .iter()
+            .positions(|c| *c == EMPTY_KEY)
+            .collect_vec();
+    }
+}
+
+impl Display for ImportReport
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result
+    {
+        let empty = self.empty_positions();
+
+        let mut format = format!("Imported {} keys.\n", 30 - empty.len());
+
+        if !self.unplaced.is_empty()
+        {
+            format.push_str("Keys that don't fit in the 30 key grid:\n");
+
+            self.unplaced.iter().for_each(|(key, source)| {
+                format.push_str(format!("  {:8} {}\n", key, source).as_str());
+            });
+        }
+
+        if !empty.is_empty()
+        {
+            format.push_str(format!("Empty positions: {}\n", empty.iter().join(", ")).as_str());
+        }
+
+        write!(f, "{}", format.trim_end())
+    }
+}
@@ -0,0 +1,210 @@
+use crate::language_data::LanguageData;
+use crate::layout::layer::EMPTY_KEY;
+use crate::stats::patterns::{
+    CharIndex,
+    Patterns,
+    KEYS,
+};
+use crate::type_def::Fixed;
+use std::collections::HashMap;
+
+/// The language data of the characters of a key set, as arrays indexed by where the characters
+/// are in the key set. N-grams with a character outside of the key set aren't in the arrays, their
+/// frequencies are added up in `leftover` instead.
+pub struct IndexedData
+{
+    pub language: String,
+    pub key_set: Fixed<char>,
+    /// Indexed by `a`.
+    pub characters: Vec<f32>,
+    /// Indexed by `a * KEYS + b`.
+    pub bigrams: Vec<f32>,
+    /// Skipgrams with 1, 2, 3 and so on keys in between, for as far as the data goes, indexed like
+    /// the bigrams.
+    pub skipgrams: Vec<Vec<f32>>,
+    /// Indexed by `(a * KEYS + b) * KEYS + c`.
+    pub trigrams: Vec<f32>,
+    pub leftover: Leftover,
+    /// The data it was made from, for the characters outside of the key set.
+    pub language_data: LanguageData,
+    index: CharIndex,
+    trigram_total: f32,
+}
+
+/// The frequencies of the n-grams with a character outside of the key set.
+#[derive(Clone, Debug, Default)]
+pub struct Leftover
+{
+    pub characters: f32,
+    pub bigrams: f32,
+    pub skipgrams: Vec<f32>,
+    pub trigrams: f32,
+}
+
+impl IndexedData
+{
+    pub fn new(language_data: LanguageData, key_set: Fixed<char>) -> Self
+    {
+        let index = CharIndex::new(&key_set);
+
+        let mut leftover = Leftover::default();
+        let mut characters = vec![0.; KEYS];
+
+        for (c, p) in language_data.characters.iter()
+        {
+            match index.get(*c)
+            {
+                | Some(i) => characters[i] += p,
+                | None => leftover.characters += p,
+            }
+        }
+
+        let (bigrams, rest) = Self::dense::<2>(&index, &language_data.bigrams);
+        leftover.bigrams = rest;
+
+        let (trigrams, rest) = Self::dense::<3>(&index, &language_data.trigrams);
+        leftover.trigrams = rest;
+
+        let mut skipgrams = Vec::new();
+
+        while let Some(data) = language_data.skipgrams_at(skipgrams.len() + 1)
+        {
+            let (dense, rest) = Self::dense::<2>(&index, data);
+
+            skipgrams.push(dense);
+            leftover.skipgrams.push(rest);
+        }
+
+        let trigram_total = trigrams.iter().sum::<f32>() + leftover.trigrams;
+
+        return Self {
+            language: language_data.language.clone(),
+            key_set,
+            characters,
+            bigrams,
+            skipgrams,
+            trigrams,
+            leftover,
+            language_data,
+            index,
+            trigram_total,
+        };
+    }
+
+    /// The n-grams of `data` as a dense array, and the frequency of the ones that aren't in it.
+    fn dense<const N: usize>(index: &CharIndex, data: &HashMap<String, f32>) -> (Vec<f32>, f32)
+    {
+        let mut dense = vec![0.; KEYS.pow(N as u32)];
+        let mut leftover = 0.;
+
+        for (ngram, p) in data.iter()
+        {
+            match Patterns::locate::<N>(index, ngram)
+            {
+                | Some(keys) => dense[keys.iter().fold(0, |i, k| i * KEYS + k)] += p,
+                | None => leftover += p,
+            }
+        }
+
+        return (dense, leftover);
+    }
+
+    /// The characters of a layout that aren't in the key set. The stats have no data for them, so
+    /// they count for nothing, however often they're typed.
+    pub fn outside_key_set(&self, chars: &Fixed<char>) -> Vec<char>
+    {
+        return chars
+            .iter()
+            .filter(|c| **c != EMPTY_KEY && self.index.get(**c).is_none())
+            .copied()
+            .collect();
+    }
+
+    /// Where the character on every position of a layout is in the key set. Characters outside
+    /// of it have none, as do the ones that are already on an earlier position.
+    pub fn keys(&self, chars: &Fixed<char>) -> Fixed<Option<usize>>
+    {
+        let mut keys = [None; KEYS];
+        let mut seen = [false; KEYS];
+
+        for (i, c) in chars.iter().enumerate()
+        {
+            if let Some(k) = self.index.get(*c)
+            {
+                if !seen[k]
+                {
+                    seen[k] = true;
+                    keys[i] = Some(k);
+                }
+            }
+        }
+
+        return keys;
+    }
+
+    /// The positions of the characters of a layout that are in the key set.
+    pub fn positions(&self, chars: &Fixed<char>) -> CharIndex
+    {
+        let mut positions = CharIndex::new(chars);
+
+        for c in chars.iter().filter(|c| self.index.get(**c).is_none())
+        {
+            positions.remove(*c);
+        }
+
+        return positions;
+    }
+
+    /// The skipgrams with `distance` keys in between, if the data has them.
+    pub fn skipgrams_at(&self, distance: usize) -> Option<&Vec<f32>>
+    {
+        return self.skipgrams.get(distance.checked_sub(1)?);
+    }
+
+    #[inline(always)]
+    pub fn bigram(&self, a: usize, b: usize) -> f32
+    {
+        return self.bigrams[a * KEYS + b];
+    }
+
+    #[inline(always)]
+    pub fn trigram(&self, a: usize, b: usize, c: usize) -> f32
+    {
+        return self.trigrams[(a * KEYS + b) * KEYS + c];
+    }
+
+    /// The frequency of all trigrams, the ones outside of the key set included.
+    pub fn trigram_total(&self) -> f32
+    {
+        return self.trigram_total;
+    }
+}
+
+#[test]
+fn indexed_data()
+{
+    let language_data = LanguageData::test()
+        .with_characters(&[('a', 0.5), ('b', 0.3), ('?', 0.2)])
+        .with_bigrams(&[("ab", 0.6), ("b?", 0.4)])
+        .with_skipgrams(1, &[("ba", 1.0)])
+        .with_trigrams(&[("aba", 0.7), ("?ab", 0.3)]);
+
+    let key_set = "abcdefghijklmnopqrstuvwxyz',.;".chars().collect::<Vec<_>>();
+    let data = IndexedData::new(language_data, key_set.try_into().unwrap());
+
+    assert_eq!(data.characters[0], 0.5);
+    assert_eq!(data.leftover.characters, 0.2);
+    assert_eq!(data.bigram(0, 1), 0.6);
+    assert_eq!(data.leftover.bigrams, 0.4);
+    assert_eq!(data.skipgrams_at(1).unwrap()[KEYS], 1.);
+    assert_eq!(data.skipgrams.len(), 3);
+    assert_eq!(data.trigram(0, 1, 0), 0.7);
+    assert_eq!(data.trigram_total(), 1.);
+
+    let chars = "abaaaaaaaaaaaaaaaaaaaaaaaaaaa?".chars().collect::<Vec<_>>().try_into().unwrap();
+    let keys = data.keys(&chars);
+
+    assert_eq!(keys[.. 3], [Some(0), Some(1), None]);
+    assert_eq!(keys[29], None);
+    assert_eq!(data.outside_key_set(&chars), ['?']);
+}
@@ -11,4 +11,22 @@ pub struct LanguageData
     pub skipgrams2: HashMap<String, f32>,
     pub skipgrams3: HashMap<String, f32>,
     pub trigrams: HashMap<String, f32>,
+    /// Skipgrams with more keys in between, named `skipgrams4`, `skipgrams5` and so on.
+    #[serde(flatten)]
+    pub more_skipgrams: HashMap<String, HashMap<String, f32>>,
+}
+
+impl LanguageData
+{
+    /// The skipgrams with `distance` keys in between, if the data has them.
+    pub fn skipgrams_at(&self, distance: usize) -> Option<&HashMap<String, f32>>
+    {
+        return match distance
+        {
+            | 1 => Some(&self.skipgrams),
+            | 2 => Some(&self.skipgrams2),
+            | 3 => Some(&self.skipgrams3),
+            | n => self.more_skipgrams.get(format!("skipgrams{n}").as_str()),
+        };
+    }
 }
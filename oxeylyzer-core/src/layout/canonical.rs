@@ -0,0 +1,169 @@
+use crate::hand::finger::Finger;
+use crate::layout::layout::Layout;
+use crate::type_def::Fixed;
+use itertools::Itertools;
+
+const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+/// A rearrangement of the grid that keeps every key on the same finger, or on the same finger of
+/// the other hand. Finger based stats like SFBs and SFS stay the same under each of them, but the
+/// effort maps and key positions of a keyboard type aren't symmetric, so the heatmap, finger speed
+/// and lateral stretches can change, and with them the score.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Symmetry
+{
+    /// Flips the layout left to right, swapping the hands.
+    Mirror,
+    /// Swaps the inner and outer index column of the left hand.
+    LeftIndexSwap,
+    /// Swaps the inner and outer index column of the right hand.
+    RightIndexSwap,
+}
+
+impl Symmetry
+{
+    pub const ALL: [Symmetry; 3] = [
+        Symmetry::Mirror,
+        Symmetry::LeftIndexSwap,
+        Symmetry::RightIndexSwap,
+    ];
+
+    /// The column each column ends up in.
+    const fn column(&self, column: usize) -> usize
+    {
+        return match (self, column)
+        {
+            | (Symmetry::Mirror, c) => 9 - c,
+            | (Symmetry::LeftIndexSwap, 3) => 4,
+            | (Symmetry::LeftIndexSwap, 4) => 3,
+            | (Symmetry::RightIndexSwap, 5) => 6,
+            | (Symmetry::RightIndexSwap, 6) => 5,
+            | (_, c) => c,
+        };
+    }
+
+    pub fn apply<T: Copy>(&self, matrix: &[T; 30]) -> [T; 30]
+    {
+        let mut result = *matrix;
+
+        for (i, value) in matrix.iter().enumerate()
+        {
+            result[i / 10 * 10 + self.column(i % 10)] = *value;
+        }
+
+        return result;
+    }
+}
+
+impl Layout
+{
+    /// Applies a symmetry to the base grid and every layer. Mirroring also moves layer keys to
+    /// the same finger on the other hand.
+    pub fn transformed(&self, symmetry: Symmetry) -> Layout
+    {
+        let mut layout = self.clone();
+
+        layout.matrix = symmetry.apply(&self.matrix);
+
+        for layer in layout.layers.iter_mut()
+        {
+            layer.matrix = symmetry.apply(&layer.matrix);
+
+            if symmetry == Symmetry::Mirror
+            {
+                layer.trigger = Finger::from(9 - layer.trigger as u8);
+            }
+        }
+
+        return layout;
+    }
+
+    pub fn mirrored(&self) -> Layout
+    {
+        return self.transformed(Symmetry::Mirror);
+    }
+
+    /// Every distinct base grid that can be made from this one with any combination of
+    /// symmetries, including the grid itself.
+    pub fn variants(&self) -> Vec<Fixed<char>>
+    {
+        return Symmetry::ALL
+            .iter()
+            .powerset()
+            .map(|symmetries| {
+                symmetries
+                    .into_iter()
+                    .fold(self.matrix, |matrix, s| s.apply(&matrix))
+            })
+            .unique()
+            .collect_vec();
+    }
+
+    /// The variant that comes first when the keys are read in order. Two layouts that only differ
+    /// by symmetries have the same canonical form, even if they don't score the same.
+    pub fn canonical(&self) -> Fixed<char>
+    {
+        return self.variants().into_iter().min().unwrap();
+    }
+
+    /// A 64 bit FNV-1a hash of the canonical form. It doesn't depend on the Rust version or
+    /// platform, so it can be stored and compared later.
+    pub fn fingerprint(&self) -> u64
+    {
+        let mut hash = FNV_OFFSET;
+        let mut buffer = [0; 4];
+
+        for c in self.canonical()
+        {
+            for byte in c.encode_utf8(&mut buffer).bytes()
+            {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+        }
+
+        return hash;
+    }
+
+    /// The least number of keys that are in a different place between this layout and any
+    /// variant of the other one.
+    pub fn distance(&self, other: &Layout) -> usize
+    {
+        return other
+            .variants()
+            .iter()
+            .map(|variant| {
+                self.matrix
+                    .iter()
+                    .zip(variant.iter())
+                    .filter(|(a, b)| a != b)
+                    .count()
+            })
+            .min()
+            .unwrap();
+    }
+}
+
+#[test]
+fn fingerprint()
+{
+    let qwerty = Layout::from([
+        'q', 'w', 'e', 'r', 't', 'y', 'u', 'i', 'o', 'p', //
+        'a', 's', 'd', 'f', 'g', 'h', 'j', 'k', 'l', ';', //
+        'z', 'x', 'c', 'v', 'b', 'n', 'm', ',', '.', '/',
+    ]);
+
+    let variant = qwerty.mirrored().transformed(Symmetry::LeftIndexSwap);
+
+    assert_eq!(qwerty.variants().len(), 8);
+    assert_eq!(qwerty.canonical(), variant.canonical());
+    assert_eq!(qwerty.fingerprint(), variant.fingerprint());
+    assert_eq!(qwerty.distance(&variant), 0);
+
+    let mut swapped = qwerty.clone();
+    swapped.matrix.swap(0, 1);
+
+    assert_eq!(qwerty.distance(&swapped), 2);
+    assert_ne!(qwerty.fingerprint(), swapped.fingerprint());
+}
@@ -7,10 +7,18 @@ use crate::layout::keyboard_type::KeyboardType::{
 };
 use crate::type_def::Fixed;
 use itertools::Itertools;
-use serde::Deserialize;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use std::fmt;
+use std::fmt::{
+    Display,
+    Formatter,
+};
 
-#[derive(Deserialize, Clone, Default)]
-#[serde(from = "String")]
+#[derive(Deserialize, Serialize, Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(try_from = "String", into = "String")]
 #[serde(rename_all(deserialize = "PascalCase"))]
 pub enum KeyboardType
 {
@@ -65,37 +73,127 @@ impl KeyboardType
             },
         };
     }
+
+    /// Where the center of each key is, as `(x, y)` in key widths from the top left key.
+    pub fn key_positions(&self) -> [(f32, f32); 30]
+    {
+        // How far each row is shifted to the right.
+        let stagger = match self
+        {
+            | Ortho | Colstag => [0.0, 0.0, 0.0],
+            | _ => [0.0, 0.25, 0.75],
+        };
+
+        // How far each column is shifted down.
+        let column_stagger = match self
+        {
+            | Colstag => [0.35, 0.1, 0.0, 0.1, 0.15, 0.15, 0.1, 0.0, 0.1, 0.35],
+            | _ => [0.0; 10],
+        };
+
+        let mut positions = [(0., 0.); 30];
+
+        for (i, position) in positions.iter_mut().enumerate()
+        {
+            let (row, column) = (i / 10, i % 10);
+
+            let mut x = column as f32 + stagger[row];
+
+            // Angle mods move the bottom left keys one to the left, so the fingers stay in their
+            // columns. ISO has a key left of Z for the first one, ANSI puts it on B instead.
+            if row == 2 && column < 5
+            {
+                x = match (self, column)
+                {
+                    | (AnsiAngle, 0) => 4.0 + stagger[row],
+                    | (AnsiAngle | IsoAngle, _) => x - 1.0,
+                    | _ => x,
+                };
+            }
+
+            *position = (x, row as f32 + column_stagger[column]);
+        }
+
+        return positions;
+    }
+
+    /// The distance between every two keys in key widths, with the sideways part multiplied by
+    /// `lateral_penalty`.
+    pub fn distances(&self, lateral_penalty: f32) -> [[f32; 30]; 30]
+    {
+        let positions = self.key_positions();
+
+        return std::array::from_fn(|a| {
+            std::array::from_fn(|b| {
+                let dx = (positions[a].0 - positions[b].0) * lateral_penalty;
+                let dy = positions[a].1 - positions[b].1;
+
+                return (dx * dx + dy * dy).sqrt();
+            })
+        });
+    }
 }
 
-impl From<String> for KeyboardType
+impl TryFrom<String> for KeyboardType
 {
-    fn from(value: String) -> Self
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error>
     {
         let lower = value.to_lowercase();
         let split = lower.split_whitespace().collect_vec();
 
-        return match split.len()
+        return match split.as_slice()
         {
-            | 1 =>
-            {
-                match split[0]
-                {
-                    | "ortho" => Ortho,
-                    | "colstag" => Colstag,
-                    | "rowstag" | "iso" | "ansi" | "jis" => RowstagDefault,
-                    | _ => Self::default(),
-                }
-            },
-            | 2 =>
-            {
-                match (split[0], split[1])
-                {
-                    | ("ansi", "angle") => AnsiAngle,
-                    | ("iso", "angle") => IsoAngle,
-                    | _ => Self::default(),
-                }
-            },
-            | _ => panic!("Couldn't match keyboard type."),
+            | ["ortho"] => Ok(Ortho),
+            | ["colstag"] => Ok(Colstag),
+            | ["rowstag" | "iso" | "ansi" | "jis"] => Ok(RowstagDefault),
+            | ["ansi", "angle"] => Ok(AnsiAngle),
+            | ["iso", "angle"] => Ok(IsoAngle),
+            | _ => Err(format!(
+                "'{value}' is not a keyboard type, use one of ansi, iso, jis, rowstag, ansi angle, \
+                iso angle, ortho or colstag."
+            )),
+        };
+    }
+}
+
+impl From<KeyboardType> for String
+{
+    fn from(value: KeyboardType) -> Self
+    {
+        return value.to_string();
+    }
+}
+
+impl Display for KeyboardType
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result
+    {
+        let to_write = match self
+        {
+            | AnsiAngle => "ansi angle",
+            | IsoAngle => "iso angle",
+            | RowstagDefault => "rowstag",
+            | Ortho => "ortho",
+            | Colstag => "colstag",
         };
+
+        return write!(f, "{}", to_write);
     }
 }
+
+#[test]
+fn key_positions()
+{
+    let ansi = AnsiAngle.key_positions();
+    let iso = IsoAngle.key_positions();
+
+    // The physical B key on ANSI, the key left of Z on ISO.
+    assert_eq!(ansi[20], (4.75, 2.));
+    assert_eq!(iso[20], (-0.25, 2.));
+
+    assert_eq!(ansi[21], (0.75, 2.));
+    assert_eq!(iso[21], (0.75, 2.));
+    assert_eq!(ansi[25], (5.75, 2.));
+}
@@ -0,0 +1,103 @@
+use crate::hand::finger::Finger;
+use crate::layout::layer::Activation::{
+    Hold,
+    OneShot,
+};
+use crate::type_def::Fixed;
+use std::fmt;
+use std::fmt::{
+    Display,
+    Formatter,
+};
+use std::ops::Index;
+use std::str::FromStr;
+
+/// Marks an empty slot in a layer grid. Layers rarely fill all 30 keys.
+pub const EMPTY_KEY: char = ' ';
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Activation
+{
+    /// The layer key is held down for as long as the layer is in use.
+    Hold,
+    /// The layer key is tapped once and applies to the next keypress only.
+    OneShot,
+}
+
+impl FromStr for Activation
+{
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err>
+    {
+        return match s.to_lowercase().as_str()
+        {
+            | "hold" => Ok(Hold),
+            | "oneshot" | "one_shot" | "osl" => Ok(OneShot),
+            | _ => Err(format!("'{s}' is not a layer activation, use 'hold' or 'oneshot'.")),
+        };
+    }
+}
+
+impl Display for Activation
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result
+    {
+        let to_write = match self
+        {
+            | Hold => "hold",
+            | OneShot => "oneshot",
+        };
+
+        return write!(f, "{}", to_write);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Layer
+{
+    pub name: String,
+    pub activation: Activation,
+    /// The finger that presses the layer key.
+    pub trigger: Finger,
+    pub matrix: Fixed<char>,
+}
+
+impl Layer
+{
+    pub fn new(name: &str, activation: Activation, trigger: Finger) -> Self
+    {
+        return Self {
+            name: name.to_string(),
+            activation,
+            trigger,
+            matrix: [EMPTY_KEY; 30],
+        };
+    }
+
+    pub fn position_of(&self, c: char) -> Option<usize>
+    {
+        if c == EMPTY_KEY
+        {
+            return None;
+        }
+
+        return self.matrix.iter().position(|x| *x == c);
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &char>
+    {
+        return self.matrix.iter().filter(|c| **c != EMPTY_KEY);
+    }
+}
+
+impl Index<usize> for Layer
+{
+    type Output = char;
+
+    #[inline(always)]
+    fn index(&self, index: usize) -> &Self::Output
+    {
+        return &self.matrix[index];
+    }
+}
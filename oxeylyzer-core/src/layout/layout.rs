@@ -1,3 +1,5 @@
+use crate::layout::layer::Layer;
+use crate::layout::metadata::Metadata;
 use crate::type_def::Fixed;
 use std::collections::HashMap;
 use std::ops::Index;
@@ -8,6 +10,34 @@ pub type CharToFinger = HashMap<u8, usize>;
 pub struct Layout
 {
     pub matrix: Fixed<char>,
+    /// Extra layers on top of the base `matrix`, in the order they were declared.
+    pub layers: Vec<Layer>,
+    pub metadata: Metadata,
+    /// Keys in its `.kb` file past the 30 of a grid, which aren't on the layout.
+    pub extra: Vec<char>,
+}
+
+impl Layout
+{
+    pub fn layer(&self, name: &str) -> Option<&Layer>
+    {
+        return self.layers.iter().find(|l| l.name == name);
+    }
+
+    /// Finds the layer and position a character is typed from. The base layer is `None`.
+    pub fn locate(&self, c: char) -> Option<(Option<usize>, usize)>
+    {
+        if let Some(p) = self.matrix.iter().position(|x| *x == c)
+        {
+            return Some((None, p));
+        }
+
+        return self
+            .layers
+            .iter()
+            .enumerate()
+            .find_map(|(i, l)| l.position_of(c).map(|p| (Some(i), p)));
+    }
 }
 
 impl Index<usize> for Layout
@@ -30,6 +60,11 @@ impl From<Fixed<char>> for Layout
         //     7,
         // ];
 
-        return Self { matrix: layout };
+        return Self {
+            matrix: layout,
+            layers: Vec::new(),
+            metadata: Metadata::default(),
+            extra: Vec::new(),
+        };
     }
 }
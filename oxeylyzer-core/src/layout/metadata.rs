@@ -0,0 +1,33 @@
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+/// Optional information about a layout, stored in the header block of a v2 `.kb` file.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct Metadata
+{
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub year: Option<u32>,
+    /// The keyboard the layout was made for, e.g. `ansi angle` or `colstag`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub board: Option<String>,
+    /// A pin grid in the same format as `[pins]` in `config.toml`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pins: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+}
+
+impl Metadata
+{
+    pub fn is_empty(&self) -> bool
+    {
+        return *self == Self::default();
+    }
+}
@@ -1,2 +1,6 @@
+pub mod canonical;
 pub mod keyboard_type;
+pub mod layer;
 pub mod layout;
+pub mod metadata;
+pub mod validation;
@@ -0,0 +1,175 @@
+use crate::language_data::LanguageData;
+use crate::layout::layer::EMPTY_KEY;
+use crate::layout::layout::Layout;
+use crate::stats::layer_stats::BASE_LAYER;
+use indexmap::IndexMap;
+use itertools::Itertools;
+use std::fmt;
+use std::fmt::{
+    Display,
+    Formatter,
+};
+
+/// Problems with a layout that would make its stats wrong without any error, like a typo in its
+/// `.kb` file.
+#[derive(Debug, Clone, Default)]
+pub struct LayoutValidation
+{
+    /// Characters that are on more than one key, with the layer and position of each.
+    pub duplicates: IndexMap<char, Vec<(String, usize)>>,
+    /// Positions of the base layer without a key.
+    pub empty: Vec<usize>,
+    /// Keys past the 30 of a grid in the `.kb` file, which aren't on the layout.
+    pub extra: Vec<char>,
+    /// Characters of the language's key set that can't be typed.
+    pub missing: Vec<char>,
+    /// Characters on the layout that never appear in the corpus.
+    pub unknown: Vec<char>,
+    /// How much of the corpus, by character frequency, can't be typed.
+    pub unreachable: f32,
+}
+
+impl LayoutValidation
+{
+    pub fn new(layout: &Layout, key_set: &[char], language_data: &LanguageData) -> Self
+    {
+        let mut keys: IndexMap<char, Vec<(String, usize)>> = IndexMap::new();
+
+        let layers = [(BASE_LAYER, &layout.matrix)]
+            .into_iter()
+            .chain(layout.layers.iter().map(|l| (l.name.as_str(), &l.matrix)));
+
+        for (name, matrix) in layers
+        {
+            for (i, c) in matrix.iter().enumerate()
+            {
+                if *c != EMPTY_KEY
+                {
+                    keys.entry(*c).or_default().push((name.to_string(), i));
+                }
+            }
+        }
+
+        let empty = layout
+            .matrix
+            .iter()
+            .positions(|c| *c == EMPTY_KEY)
+            .collect_vec();
+
+        let missing = key_set
+            .iter()
+            .filter(|c| **c != EMPTY_KEY && !keys.contains_key(*c))
+            .copied()
+            .collect_vec();
+
+        let unknown = keys
+            .keys()
+            .filter(|c| !language_data.characters.contains_key(*c))
+            .copied()
+            .collect_vec();
+
+        let unreachable = language_data
+            .characters
+            .iter()
+            .filter(|(c, _)| !keys.contains_key(*c))
+            .map(|(_, f)| f)
+            .sum::<f32>()
+            * 100.;
+
+        keys.retain(|_, positions| positions.len() > 1);
+
+        return Self {
+            duplicates: keys,
+            extra: layout.extra.clone(),
+            empty,
+            missing,
+            unknown,
+            unreachable,
+        };
+    }
+
+    /// Whether the layout has all the keys of the key set, each on exactly one key, and no keys
+    /// that didn't fit on it.
+    pub fn is_valid(&self) -> bool
+    {
+        return self.duplicates.is_empty() && self.missing.is_empty() && self.extra.is_empty();
+    }
+}
+
+impl Display for LayoutValidation
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result
+    {
+        let mut format = String::new();
+
+        for (c, positions) in self.duplicates.iter()
+        {
+            let positions = positions
+                .iter()
+                .map(|(layer, i)| format!("{layer} {i}"))
+                .join(", ");
+
+            format.push_str(format!("  Duplicate '{c}' at {positions}\n").as_str());
+        }
+
+        if !self.extra.is_empty()
+        {
+            let extra = self.extra.iter().join(" ");
+
+            format.push_str(format!("  Past the 30 keys of a grid: {extra}\n").as_str());
+        }
+
+        if !self.empty.is_empty()
+        {
+            let empty = self.empty.iter().join(", ");
+
+            format.push_str(format!("  Empty positions: {empty}\n").as_str());
+        }
+
+        if !self.missing.is_empty()
+        {
+            let missing = self.missing.iter().join(" ");
+
+            format.push_str(format!("  Missing: {missing}\n").as_str());
+        }
+
+        if !self.unknown.is_empty()
+        {
+            let unknown = self.unknown.iter().join(" ");
+
+            format.push_str(format!("  Not in the corpus: {unknown}\n").as_str());
+        }
+
+        format.push_str(format!("  Unreachable: {:.3}%", self.unreachable).as_str());
+
+        write!(f, "{}", format)
+    }
+}
+
+#[test]
+fn validation()
+{
+    use crate::data_dir::DataFetch;
+
+    let language_data = LanguageData::test()
+        .with_characters(&[('a', 0.5), ('b', 0.3), ('c', 0.15), ('d', 0.05)]);
+
+    let layout = DataFetch::parse_layout(
+        "a b a x __ __ __ __ __ __\n\
+        __ __ __ __ __ __ __ __ __ __\n\
+        __ __ __ __ __ __ __ __ __ __ y z\n",
+    )
+    .unwrap();
+
+    let key_set = ['a', 'b', 'c'];
+    let validation = LayoutValidation::new(&layout, &key_set, &language_data);
+
+    assert_eq!(validation.duplicates[&'a'], [("base".to_string(), 0), ("base".to_string(), 2)]);
+    assert_eq!(validation.duplicates.len(), 1);
+    assert_eq!(validation.extra, ['y', 'z']);
+    assert_eq!(validation.missing, ['c']);
+    assert_eq!(validation.unknown, ['x']);
+    assert_eq!(validation.empty.len(), 26);
+    assert!((validation.unreachable - 20.).abs() < 1e-4);
+    assert!(!validation.is_valid());
+}
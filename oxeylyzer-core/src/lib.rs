@@ -4,10 +4,17 @@ pub use rayon;
 pub use serde;
 
 pub mod config;
+pub mod corpus_config;
 pub mod data_dir;
+pub mod export;
+pub mod generation;
 pub mod hand;
+pub mod import;
+pub mod indexed_data;
 pub mod language_data;
 pub mod layout;
 pub mod stats;
+#[cfg(test)]
+mod testing;
 pub mod type_def;
 pub mod utility;
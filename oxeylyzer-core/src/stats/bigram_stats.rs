@@ -1,10 +1,14 @@
-use crate::language_data::LanguageData;
+use crate::indexed_data::IndexedData;
 use crate::stats::bigram_stats::BType::*;
-use crate::stats::layout_stats::LayoutStats;
+use crate::stats::patterns::{
+    BigramPattern,
+    KEYS,
+    PATTERNS,
+};
 use crate::type_def::Fixed;
 use indexmap::IndexMap;
+use itertools::Itertools;
 pub use std::collections::hash_map::Entry;
-use std::collections::HashMap;
 use std::fmt;
 use std::fmt::{
     Display,
@@ -26,17 +30,19 @@ pub enum BType
 
 impl BType
 {
-    fn f(&self) -> fn(a: &mut [u8]) -> bool
+    pub const ALL: [BType; 7] = [SFB, LSB, IRB, ORB, AB, Repeat, S];
+
+    pub(crate) const fn pattern(&self) -> BigramPattern
     {
         return match self
         {
-            | SFB => LayoutStats::is_sf,
-            | LSB => LayoutStats::is_lsb,
-            | IRB => LayoutStats::is_sf,
-            | ORB => LayoutStats::is_sf,
-            | AB => LayoutStats::is_sf,
-            | Repeat => LayoutStats::is_sf,
-            | S => LayoutStats::is_sf,
+            | SFB => BigramPattern::SFB,
+            | LSB => BigramPattern::LSB,
+            | IRB => BigramPattern::IRB,
+            | ORB => BigramPattern::ORB,
+            | AB => BigramPattern::AB,
+            | Repeat => BigramPattern::Repeat,
+            | S => BigramPattern::S,
         };
     }
 }
@@ -59,25 +65,15 @@ impl Index<BType> for BigramStats
 
 impl BigramStats
 {
-    pub fn new(language_data: &LanguageData, chars: &Fixed<char>, a: &[BType]) -> Self
+    pub fn new(data: &IndexedData, chars: &Fixed<char>, a: &[BType]) -> Self
     {
+        let keys = data.keys(chars);
+
         let mut stats = IndexMap::new();
 
         for t in a
         {
-            let p = match t
-            {
-                | SFB => Self::p1(chars, &language_data.bigrams, LayoutStats::is_sf),
-                | LSB => Self::p1(chars, &language_data.bigrams, LayoutStats::is_lsb),
-                | Repeat => Self::p1(chars, &language_data.bigrams, LayoutStats::is_repeat),
-                | S1SFB => Self::p1(chars, &language_data.skipgrams, LayoutStats::is_sf),
-                | S2SFB => Self::p1(chars, &language_data.skipgrams2, LayoutStats::is_sf),
-                | S3SFB => Self::p1(chars, &language_data.skipgrams3, LayoutStats::is_sf),
-                | IRB => Self::p1(chars, &language_data.bigrams, LayoutStats::is_inroll),
-                | ORB => Self::p1(chars, &language_data.bigrams, LayoutStats::is_outroll),
-                | S => Self::p1(chars, &language_data.bigrams, LayoutStats::is_scissor),
-                | AB => Self::p1(chars, &language_data.bigrams, LayoutStats::is_alternate),
-            };
+            let p = Self::p1(chars, &keys, &data.bigrams, t.pattern());
 
             stats.insert(*t, p);
         }
@@ -85,67 +81,30 @@ impl BigramStats
         return Self { inner: stats };
     }
 
+    /// The frequency of the bigrams in `data` whose positions on the layout make `pattern`, in
+    /// percent. `keys` are the key set indices of the layout's characters, see
+    /// `IndexedData::keys`. Bigrams with punctuation are left out.
     pub(crate) fn p1(
         chars: &Fixed<char>,
-        data: &HashMap<String, f32>,
-        f: fn(&mut [u8]) -> bool,
-    ) -> f32
-    {
-        use rayon::iter::*;
-
-        let a = (0 .. 30).into_par_iter().map(|i| {
-            let b = (0 .. 30).into_par_iter().map(|j| {
-                if f(&mut [i as u8, j as u8])
-                {
-                    let c0 = chars[i];
-                    let c1 = chars[j];
-
-                    if [c0, c1].iter().any(char::is_ascii_punctuation)
-                    {
-                        return 0.;
-                    }
-
-                    let p = data.get(&format!("{}{}", c0, c1)).unwrap_or(&0.);
-
-                    return *p;
-                }
-
-                return 0.;
-            });
-
-            return b.collect::<Vec<f32>>().iter().sum();
-        });
-
-        let q: f32 = a.collect::<Vec<f32>>().iter().sum();
-
-        return q * 100.;
-    }
-
-    pub(crate) fn p(
-        chars: &Fixed<char>,
-        data: &HashMap<String, f32>,
-        f: fn(&mut [u8]) -> bool,
+        keys: &Fixed<Option<usize>>,
+        data: &[f32],
+        pattern: BigramPattern,
     ) -> f32
     {
         let mut res = 0.;
 
-        for i in 0 .. 30
+        for (a, b) in (0 .. KEYS).cartesian_product(0 .. KEYS)
         {
-            for j in 0 .. 30
+            if !PATTERNS.is_bigram(a, b, pattern)
             {
-                if f(&mut [i as u8, j as u8])
-                {
-                    let c0 = chars[i];
-                    let c1 = chars[j];
-
-                    if [c0, c1].iter().any(char::is_ascii_punctuation)
-                    {
-                        continue;
-                    }
-
-                    let p = data.get(&format!("{}{}", c0, c1)).unwrap_or(&0.0);
+                continue;
+            }
 
-                    res += p;
+            if let (Some(ka), Some(kb)) = (keys[a], keys[b])
+            {
+                if !chars[a].is_ascii_punctuation() && !chars[b].is_ascii_punctuation()
+                {
+                    res += data[ka * KEYS + kb];
                 }
             }
         }
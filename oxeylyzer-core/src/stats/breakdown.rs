@@ -0,0 +1,270 @@
+use crate::indexed_data::IndexedData;
+use crate::stats::bigram_stats::BType;
+use crate::stats::disjoint_stats::DType;
+use crate::stats::layout_stats::LayoutStats;
+use crate::stats::patterns::{
+    BigramPattern,
+    CharIndex,
+    Patterns,
+    PATTERNS,
+};
+use crate::stats::skip_stats::SkipType;
+use crate::stats::trigram_stats::{
+    TStats,
+    TType,
+};
+use crate::type_def::Fixed;
+use crate::utility::scorer::Scorer;
+use itertools::Itertools;
+use std::collections::HashMap;
+use std::fmt;
+use std::fmt::{
+    Display,
+    Formatter,
+};
+use std::str::FromStr;
+
+/// Any stat that is a sum of n-gram frequencies.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+pub enum Metric
+{
+    Bigram(BType),
+    Trigram(TType),
+    Disjoint(DType),
+    /// Skipgrams with this many keys in between.
+    Skip(usize, SkipType),
+}
+
+/// The n-grams that make up a metric on a layout, with their frequencies in percent and what they
+/// add to the score, the biggest impact first. The frequencies add up to the value of the metric
+/// in `LayoutStats`.
+#[derive(Clone, Debug)]
+pub struct Breakdown
+{
+    pub metric: Metric,
+    pub ngrams: Vec<(String, f32, f32)>,
+}
+
+impl Breakdown
+{
+    /// Same finger bigrams and scissors on pinkies and rings are weighed with the `bad` weights,
+    /// like the scorer does.
+    pub fn new(data: &IndexedData, chars: &Fixed<char>, metric: Metric, scorer: &Scorer) -> Self
+    {
+        let language_data = &data.language_data;
+        let positions = data.positions(chars);
+
+        let frequencies = match metric
+        {
+            | Metric::Bigram(t) => Self::bigrams(&positions, &language_data.bigrams, t.pattern()),
+            | Metric::Trigram(t) => Self::trigrams(&positions, &language_data.trigrams, t),
+            | Metric::Disjoint(t) =>
+            {
+                Self::disjoints(&positions, &language_data.trigrams, t.pattern())
+            },
+            | Metric::Skip(n, t) => match language_data.skipgrams_at(n)
+            {
+                | None => Vec::new(),
+                | Some(skipgrams) => Self::bigrams(&positions, skipgrams, t.pattern()),
+            },
+        };
+
+        let bad = match metric
+        {
+            | Metric::Bigram(BType::SFB) => Some(BigramPattern::BadSFB),
+            | Metric::Bigram(BType::S) => Some(BigramPattern::BadS),
+            | _ => None,
+        };
+
+        let is_bad = |ngram: &str| {
+            bad.is_some_and(|pattern| {
+                Patterns::locate(&positions, ngram)
+                    .is_some_and(|[a, b]| PATTERNS.is_bigram(a, b, pattern))
+            })
+        };
+
+        let mut ngrams = frequencies
+            .into_iter()
+            .map(|(ngram, f)| {
+                let impact = f * scorer.weight(metric, is_bad(ngram.as_str()));
+
+                (ngram, f, impact)
+            })
+            .collect_vec();
+
+        ngrams.sort_by(|(s0, f0, i0), (s1, f1, i1)| {
+            i1.abs().total_cmp(&i0.abs()).then(f1.total_cmp(f0)).then(s0.cmp(s1))
+        });
+
+        return Self { metric, ngrams };
+    }
+
+    /// The frequency of the metric in percent.
+    pub fn total(&self) -> f32
+    {
+        return self.ngrams.iter().map(|(_, f, _)| f).sum();
+    }
+
+    /// What the metric adds to the score.
+    pub fn impact(&self) -> f32
+    {
+        return self.ngrams.iter().map(|(_, _, i)| i).sum();
+    }
+
+    fn bigrams(
+        positions: &CharIndex,
+        data: &HashMap<String, f32>,
+        pattern: BigramPattern,
+    ) -> Vec<(String, f32)>
+    {
+        return Self::matching(data, |bigram| {
+            Patterns::locate(positions, bigram)
+                .is_some_and(|[a, b]| PATTERNS.is_bigram(a, b, pattern))
+                && !bigram.chars().any(|c| c.is_ascii_punctuation())
+        });
+    }
+
+    fn trigrams(positions: &CharIndex, data: &HashMap<String, f32>, t: TType) -> Vec<(String, f32)>
+    {
+        return Self::matching(data, |trigram| TStats::classify(positions, trigram) == t);
+    }
+
+    /// Trigrams that alternate hands twice, where the first and last key make up the bigram.
+    fn disjoints(
+        positions: &CharIndex,
+        data: &HashMap<String, f32>,
+        pattern: BigramPattern,
+    ) -> Vec<(String, f32)>
+    {
+        let left = |i: &usize| LayoutStats::is_left_hand(i);
+
+        return Self::matching(data, |trigram| {
+            Patterns::locate(positions, trigram).is_some_and(|[i, j, k]| {
+                left(&i) != left(&j) && left(&j) != left(&k) && PATTERNS.is_bigram(i, k, pattern)
+            }) && !trigram.chars().any(|c| c.is_ascii_punctuation())
+        });
+    }
+
+    /// The n-grams that occur and match, with their frequency in percent.
+    fn matching(data: &HashMap<String, f32>, f: impl Fn(&str) -> bool) -> Vec<(String, f32)>
+    {
+        return data
+            .iter()
+            .filter(|(ngram, p)| **p > 0. && f(ngram))
+            .map(|(ngram, p)| (ngram.clone(), p * 100.))
+            .collect_vec();
+    }
+}
+
+impl FromStr for Metric
+{
+    type Err = String;
+
+    /// Reads a metric by the name it has in the stats, e.g. `SFB`, `Redirect`, `D1LSB` or
+    /// `S2SFB`. Case doesn't matter.
+    fn from_str(s: &str) -> Result<Self, Self::Err>
+    {
+        let is = |name: String| name.eq_ignore_ascii_case(s);
+
+        let metric = BType::ALL
+            .into_iter()
+            .find(|t| is(format!("{t:?}")))
+            .map(Metric::Bigram)
+            .or_else(|| TType::ALL.into_iter().find(|t| is(format!("{t:?}"))).map(Metric::Trigram))
+            .or_else(|| {
+                DType::ALL
+                    .into_iter()
+                    .find(|t| is(format!("{t:?}")))
+                    .map(Metric::Disjoint)
+            })
+            .or_else(|| {
+                (1 ..= 9)
+                    .cartesian_product(SkipType::ALL)
+                    .find(|(n, t)| is(format!("S{n}{t:?}")))
+                    .map(|(n, t)| Metric::Skip(n, t))
+            });
+
+        return metric.ok_or(format!(
+            "\"{s}\" is not a metric. Use the name of a bigram, trigram, disjoint or skipgram \
+            stat, e.g. SFB, Redirect, D1LSB or S2SFB."
+        ));
+    }
+}
+
+impl Display for Metric
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result
+    {
+        return match self
+        {
+            | Metric::Bigram(t) => write!(f, "{t:?}"),
+            | Metric::Trigram(t) => write!(f, "{t:?}"),
+            | Metric::Disjoint(t) => write!(f, "{t:?}"),
+            | Metric::Skip(n, t) => write!(f, "S{n}{t:?}"),
+        };
+    }
+}
+
+#[test]
+fn parse_metric()
+{
+    assert_eq!("sfb".parse(), Ok(Metric::Bigram(BType::SFB)));
+    assert_eq!("S".parse(), Ok(Metric::Bigram(BType::S)));
+    assert_eq!("redirect".parse(), Ok(Metric::Trigram(TType::Redirect)));
+    assert_eq!("D1LSB".parse(), Ok(Metric::Disjoint(DType::D1LSB)));
+    assert_eq!("S3S".parse(), Ok(Metric::Skip(3, SkipType::S)));
+    assert!("S2".parse::<Metric>().is_err());
+}
+
+#[test]
+fn breakdown()
+{
+    use crate::config::config::Config;
+    use crate::language_data::LanguageData;
+    use crate::layout::layout::Layout;
+    use crate::testing::assert_close;
+
+    // `ak` is on a pinky, `cm` on a middle finger, and `abc` rolls in on the left hand.
+    let language_data = LanguageData::test()
+        .with_bigrams(&[("ak", 0.3), ("cm", 0.5), ("ab", 0.2)])
+        .with_skipgrams(1, &[("ak", 0.4), ("ab", 0.6)])
+        .with_trigrams(&[("abc", 0.7), ("akb", 0.3)]);
+
+    let chars: Fixed<char> = "abcdefghijklmnopqrstuvwxyz',.;"
+        .chars()
+        .collect_vec()
+        .try_into()
+        .unwrap();
+
+    let data = IndexedData::new(language_data, chars);
+
+    let layout = Layout {
+        matrix: chars,
+        ..Default::default()
+    };
+
+    let mut config = Config::default();
+    config.bigrams.same_finger.base = -1.;
+    config.bigrams.same_finger.bad = -10.;
+
+    let scorer = Scorer::new(&config);
+    let stats = LayoutStats::new(&data, &layout);
+    let score = scorer.score(&data, &layout, &stats);
+
+    let sfb = Breakdown::new(&data, &chars, Metric::Bigram(BType::SFB), &scorer);
+
+    // `ak` is less frequent, but weighs more.
+    assert_eq!(sfb.ngrams.iter().map(|(s, _, _)| s.as_str()).collect_vec(), ["ak", "cm"]);
+    assert_close(sfb.total(), stats[BType::SFB]);
+    assert_close(sfb.impact(), score.terms["SFB"].score() + score.terms["Bad SFB"].score());
+
+    let roll = Breakdown::new(&data, &chars, Metric::Trigram(TType::OneHandIn), &scorer);
+
+    assert_close(roll.total(), stats[TType::OneHandIn]);
+    assert_close(roll.impact(), score.terms["OneHandIn"].score());
+
+    let skip = Breakdown::new(&data, &chars, Metric::Skip(1, SkipType::SFB), &scorer);
+
+    assert_close(skip.total(), stats.skip1_stats[SkipType::SFB]);
+    assert_close(skip.impact(), score.terms["S1SFB"].score());
+}
@@ -1,10 +1,15 @@
-use crate::language_data::LanguageData;
+use crate::indexed_data::IndexedData;
 use crate::stats::disjoint_stats::DType::*;
 use crate::stats::layout_stats::LayoutStats;
+use crate::stats::patterns::{
+    BigramPattern,
+    KEYS,
+    PATTERNS,
+};
 use crate::type_def::Fixed;
 use indexmap::map::Entry;
 use indexmap::IndexMap;
-use std::collections::HashMap;
+use itertools::Itertools;
 use std::fmt;
 use std::fmt::{
     Display,
@@ -25,16 +30,18 @@ pub enum DType
 
 impl DType
 {
-    fn f(&self) -> fn(a: &mut [u8]) -> bool
+    pub const ALL: [DType; 6] = [D1SFB, D1LSB, D1IRB, D1ORB, D1Repeat, D1S];
+
+    pub(crate) const fn pattern(&self) -> BigramPattern
     {
         return match self
         {
-            | D1SFB => LayoutStats::is_sf,
-            | D1LSB => LayoutStats::is_lsb,
-            | D1IRB => LayoutStats::is_inroll,
-            | D1ORB => LayoutStats::is_outroll,
-            | D1Repeat => LayoutStats::is_repeat,
-            | D1S => LayoutStats::is_scissor,
+            | D1SFB => BigramPattern::SFB,
+            | D1LSB => BigramPattern::LSB,
+            | D1IRB => BigramPattern::IRB,
+            | D1ORB => BigramPattern::ORB,
+            | D1Repeat => BigramPattern::Repeat,
+            | D1S => BigramPattern::S,
         };
     }
 }
@@ -47,7 +54,7 @@ pub struct D1Stats
 
 impl D1Stats
 {
-    pub fn new(language_data: &LanguageData, chars: &Fixed<char>, a: &[DType]) -> Self
+    pub fn new(data: &IndexedData, chars: &Fixed<char>, a: &[DType]) -> Self
     {
         let mut stats = IndexMap::new();
 
@@ -56,182 +63,70 @@ impl D1Stats
             stats.insert(*t, 0.);
         }
 
-        Self::p2(chars, &language_data.trigrams, &mut stats, a);
+        Self::p2(data, chars, &mut stats, a);
 
         return Self { inner: stats };
     }
 
+    /// Adds up the trigrams that alternate hands twice, by the pattern of their first and last
+    /// key.
     pub(crate) fn p2(
+        data: &IndexedData,
         chars: &Fixed<char>,
-        data: &HashMap<String, f32>,
         index_map: &mut IndexMap<DType, f32>,
         a: &[DType],
     )
     {
-        for i in 0 .. 30
-        {
-            let i_left = LayoutStats::is_left_hand(&i);
-
-            for j in 0 .. 30
-            {
-                let j_left = LayoutStats::is_left_hand(&j);
-
-                if i_left == j_left
-                {
-                    continue;
-                }
-
-                for k in 0 .. 30
-                {
-                    for t in a
-                    {
-                        if t.f()(&mut [i as u8, k as u8])
-                        {
-                            let k_left = LayoutStats::is_left_hand(&k);
-
-                            if j_left == k_left
-                            {
-                                continue;
-                            }
+        let keys = data.keys(chars);
+        let left = |i: usize| LayoutStats::is_left_hand(&i);
 
-                            let c0 = chars[i];
-                            let c1 = chars[j];
-                            let c2 = chars[k];
+        let mut totals = vec![0.; a.len()];
 
-                            if [c0, c1, c2].iter().any(char::is_ascii_punctuation)
-                            {
-                                continue;
-                            }
-
-                            let p = data.get(&format!("{}{}{}", c0, c1, c2)).unwrap_or(&0.0);
-
-                            match index_map.entry(*t)
-                            {
-                                | Entry::Occupied(mut e) =>
-                                {
-                                    *e.get_mut() += p;
-                                },
-                                | Entry::Vacant(_) =>
-                                {
-                                    panic!();
-                                },
-                            }
-                        }
-                    }
-                }
+        for ((i, j), k) in (0 .. KEYS).cartesian_product(0 .. KEYS).cartesian_product(0 .. KEYS)
+        {
+            if left(i) == left(j) || left(j) == left(k)
+            {
+                continue;
             }
-        }
 
-        index_map.values_mut().for_each(|mut x| *x *= 100.);
-    }
-
-    pub(crate) fn p1(
-        chars: &Fixed<char>,
-        data: &HashMap<String, f32>,
-        f: fn(&mut [u8]) -> bool,
-    ) -> f32
-    {
-        use rayon::iter::*;
+            let (ki, kj, kk) = match (keys[i], keys[j], keys[k])
+            {
+                | (Some(ki), Some(kj), Some(kk)) => (ki, kj, kk),
+                | _ => continue,
+            };
 
-        let a = (0 .. 30).into_par_iter().map(|i| {
-            let i_left = LayoutStats::is_left_hand(&i);
+            if [i, j, k].iter().any(|x| chars[*x].is_ascii_punctuation())
+            {
+                continue;
+            }
 
-            let b = (0 .. 30).into_par_iter().map(|j| {
-                let j_left = LayoutStats::is_left_hand(&j);
+            let p = data.trigram(ki, kj, kk);
 
-                if i_left == j_left
+            for (total, t) in totals.iter_mut().zip(a)
+            {
+                if PATTERNS.is_bigram(i, k, t.pattern())
                 {
-                    return 0.;
+                    *total += p;
                 }
+            }
+        }
 
-                let c = (0 .. 30).into_par_iter().map(|k| {
-                    let k_left = LayoutStats::is_left_hand(&k);
-
-                    if j_left == k_left
-                    {
-                        return 0.;
-                    }
-
-                    if f(&mut [i as u8, k as u8])
-                    {
-                        let c0 = chars[i];
-                        let c1 = chars[j];
-                        let c2 = chars[k];
-
-                        if [c0, c1, c2].iter().any(char::is_ascii_punctuation)
-                        {
-                            return 0.;
-                        }
-
-                        let p = data.get(&format!("{}{}{}", c0, c1, c2)).unwrap_or(&0.);
-
-                        return *p;
-                    }
-
-                    return 0.;
-                });
-
-                return c.collect::<Vec<f32>>().iter().sum();
-            });
-
-            return b.collect::<Vec<f32>>().iter().sum();
-        });
-
-        let q: f32 = a.collect::<Vec<f32>>().iter().sum();
-
-        return q * 100.;
-    }
-
-    pub(crate) fn p(
-        chars: &Fixed<char>,
-        data: &HashMap<String, f32>,
-        f: fn(&mut [u8]) -> bool,
-    ) -> f32
-    {
-        let mut res = 0.;
-
-        for i in 0 .. 30
+        for (t, total) in a.iter().zip(totals)
         {
-            let i_left = LayoutStats::is_left_hand(&i);
-
-            for j in 0 .. 30
+            match index_map.entry(*t)
             {
-                let j_left = LayoutStats::is_left_hand(&j);
-
-                if i_left == j_left
+                | Entry::Occupied(mut e) =>
                 {
-                    continue;
-                }
-
-                for k in 0 .. 30
+                    *e.get_mut() += total;
+                },
+                | Entry::Vacant(_) =>
                 {
-                    let k_left = LayoutStats::is_left_hand(&k);
-
-                    if j_left == k_left
-                    {
-                        continue;
-                    }
-
-                    if f(&mut [i as u8, k as u8])
-                    {
-                        let c0 = chars[i];
-                        let c1 = chars[j];
-                        let c2 = chars[k];
-
-                        if [c0, c1, c2].iter().any(char::is_ascii_punctuation)
-                        {
-                            continue;
-                        }
-
-                        let p = data.get(&format!("{}{}{}", c0, c1, c2)).unwrap_or(&0.0);
-
-                        res += p;
-                    }
-                }
+                    panic!();
+                },
             }
         }
 
-        return res * 100.;
+        index_map.values_mut().for_each(|x| *x *= 100.);
     }
 }
 
@@ -0,0 +1,150 @@
+use crate::config::config::Config;
+use crate::hand::finger::Finger;
+use crate::hand::finger::Finger::*;
+use crate::indexed_data::IndexedData;
+use crate::layout::layout::Layout;
+use crate::stats::patterns::KEYS;
+use indexmap::IndexMap;
+use std::fmt;
+use std::fmt::{
+    Display,
+    Formatter,
+};
+
+/// Skipgrams with up to this many keys in between count towards finger speed.
+const MAX_SKIP: usize = 3;
+
+/// Finger speed: the frequency of same finger bigrams and skipgrams times the distance between
+/// their keys, divided by how strong the finger is relative to the strongest one.
+#[derive(Default, Clone)]
+pub struct FspeedStats
+{
+    pub fingers: IndexMap<Finger, f32>,
+}
+
+impl FspeedStats
+{
+    pub fn new(data: &IndexedData, layout: &Layout, config: &Config) -> Self
+    {
+        let fspeed = &config.effort.fspeed;
+        let preference = &config.effort.fingers.preference;
+        let finger_to_column = &config.finger_to_column;
+        let distances = config.info.keyboard_type.distances(fspeed.lateral_penalty);
+        let keys = data.keys(&layout.matrix);
+
+        let mut fingers = IndexMap::from_iter((0 .. 10).map(|f| (Finger::from(f), 0.)));
+
+        for n in 0 ..= MAX_SKIP
+        {
+            let (ngrams, weight) = match n
+            {
+                | 0 => (Some(&data.bigrams), 1.),
+                | n => (
+                    data.skipgrams_at(n),
+                    fspeed.dsfb_ratio * (n as f32).powf(-fspeed.skip_decay),
+                ),
+            };
+
+            let ngrams = match ngrams
+            {
+                | None => continue,
+                | Some(ngrams) => ngrams,
+            };
+
+            for i in 0 .. 30
+            {
+                let finger = finger_to_column[i];
+                let strength = preference.of(finger);
+
+                if strength <= 0.
+                {
+                    continue;
+                }
+
+                let weight = weight * preference.strongest() / strength;
+
+                for j in (0 .. 30).filter(|j| *j != i && finger_to_column[*j] == finger)
+                {
+                    let (k0, k1) = match (keys[i], keys[j])
+                    {
+                        | (Some(k0), Some(k1)) => (k0, k1),
+                        | _ => continue,
+                    };
+
+                    if [i, j].iter().any(|x| layout.matrix[*x].is_ascii_punctuation())
+                    {
+                        continue;
+                    }
+
+                    let p = ngrams[k0 * KEYS + k1];
+                    fingers[&finger] += p * 100. * distances[i][j] * weight;
+                }
+            }
+        }
+
+        return Self { fingers };
+    }
+
+    pub fn total(&self) -> f32
+    {
+        return self.fingers.values().sum();
+    }
+}
+
+impl Display for FspeedStats
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result
+    {
+        let mut format = "Fspeed:\n".to_string();
+
+        // Thumbs have no keys on the layout, so they're left out.
+        for (l, r) in [(LP, RP), (LR, RR), (LM, RM), (LI, RI)]
+        {
+            let s = format!(
+                "  {:6} {:>6.3}  {:6} {:>6.3}\n",
+                format!("{:?}", l),
+                self.fingers[&l],
+                format!("{:?}", r),
+                self.fingers[&r]
+            );
+
+            format.push_str(s.as_str());
+        }
+
+        format.push_str(format!("  {:6} {:>6.3}\n", "Total", self.total()).as_str());
+
+        write!(f, "{}", format)
+    }
+}
+
+#[test]
+fn fspeed_stats()
+{
+    use crate::language_data::LanguageData;
+    use crate::layout::keyboard_type::KeyboardType;
+    use crate::testing::assert_close;
+    use itertools::Itertools;
+
+    let language_data = LanguageData::test()
+        .with_bigrams(&[("ak", 0.2), ("au", 0.1), ("do", 0.3), ("ab", 0.4)])
+        .with_skipgrams(1, &[("ka", 0.5)]);
+
+    let chars = "abcdefghijklmnopqrstuvwxyz',.;".chars().collect_vec();
+    let data = IndexedData::new(language_data, chars.clone().try_into().unwrap());
+    let layout = Layout::from(<[char; 30]>::try_from(chars).unwrap());
+
+    let mut config = Config::default();
+    config.info.keyboard_type = KeyboardType::Ortho;
+    config.effort.fspeed.lateral_penalty = 2.;
+    config.effort.fspeed.dsfb_ratio = 0.5;
+    config.effort.fingers.preference.pinky = 10.;
+
+    let stats = FspeedStats::new(&data, &layout, &config);
+
+    // `a`, `k` and `u` are one key apart down the pinky column, and the pinky is half as strong
+    // as the index, which is the strongest. `d` and `o` are one key apart sideways, doubled by
+    // the lateral penalty, and one down.
+    assert_close(stats.fingers[&LP], (20. * 1. + 10. * 2. + 50. * 1. * 0.5) * 2.);
+    assert_close(stats.fingers[&LI], 30. * 5f32.sqrt());
+    assert_close(stats.total(), 130. + 30. * 5f32.sqrt());
+}
@@ -0,0 +1,48 @@
+use crate::indexed_data::IndexedData;
+use crate::layout::keyboard_type::KeyboardType;
+use crate::layout::layout::Layout;
+use crate::type_def::Fixed;
+use std::fmt;
+use std::fmt::{
+    Display,
+    Formatter,
+};
+
+/// The effort of every key of a layout: the frequency of its character in percent times the
+/// effort of its position, from the effort map of the keyboard type.
+#[derive(Default, Clone)]
+pub struct HeatmapStats
+{
+    pub positions: Fixed<f32>,
+}
+
+impl HeatmapStats
+{
+    pub fn new(data: &IndexedData, layout: &Layout, keyboard_type: KeyboardType) -> Self
+    {
+        let effort = keyboard_type.get_effort_map();
+        let keys = data.keys(&layout.matrix);
+
+        let mut positions = [0.; 30];
+
+        for (i, key) in keys.iter().enumerate()
+        {
+            positions[i] = key.map_or(0., |k| data.characters[k]) * 100. * effort[i];
+        }
+
+        return Self { positions };
+    }
+
+    pub fn total(&self) -> f32
+    {
+        return self.positions.iter().sum();
+    }
+}
+
+impl Display for HeatmapStats
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result
+    {
+        write!(f, "Heatmap:\n  {:6} {:>7.3}\n", "Total", self.total())
+    }
+}
@@ -0,0 +1,161 @@
+use crate::language_data::LanguageData;
+use crate::layout::layer::Activation::Hold;
+use crate::layout::layout::Layout;
+use indexmap::IndexMap;
+use std::fmt;
+use std::fmt::{
+    Display,
+    Formatter,
+};
+
+pub const BASE_LAYER: &str = "base";
+
+#[derive(Default, Clone)]
+pub struct LayerStats
+{
+    /// Share of all keypresses typed from each layer, the base layer included.
+    pub usage: IndexMap<String, f32>,
+    /// How often each extra layer's key has to be pressed, relative to all keypresses.
+    pub activation: IndexMap<String, f32>,
+}
+
+impl LayerStats
+{
+    pub fn new(language_data: &LanguageData, layout: &Layout) -> Self
+    {
+        let mut usage = IndexMap::new();
+        let mut activation = IndexMap::new();
+
+        usage.insert(BASE_LAYER.to_string(), 0.);
+
+        for layer in layout.layers.iter()
+        {
+            usage.insert(layer.name.clone(), 0.);
+        }
+
+        for (c, p) in language_data.characters.iter()
+        {
+            if let Some((layer, _)) = layout.locate(*c)
+            {
+                let name = match layer
+                {
+                    | None => BASE_LAYER,
+                    | Some(i) => layout.layers[i].name.as_str(),
+                };
+
+                usage[name] += p;
+            }
+        }
+
+        for (i, layer) in layout.layers.iter().enumerate()
+        {
+            let mut presses = usage[layer.name.as_str()];
+
+            // A held layer key stays down between consecutive keys on the same layer,
+            // while a one-shot key has to be tapped again for every one of them.
+            if layer.activation == Hold
+            {
+                presses -= Self::consecutive(language_data, layout, i);
+            }
+
+            activation.insert(layer.name.clone(), presses);
+        }
+
+        usage.values_mut().for_each(|x| *x *= 100.);
+        activation.values_mut().for_each(|x| *x *= 100.);
+
+        return Self { usage, activation };
+    }
+
+    /// Bigrams typed entirely from this layer. Keys that are also on the base layer or an earlier
+    /// layer are typed from there, see `Layout::locate`, so they don't count.
+    fn consecutive(language_data: &LanguageData, layout: &Layout, layer: usize) -> f32
+    {
+        let keys = layout.layers[layer]
+            .keys()
+            .filter(|c| matches!(layout.locate(**c), Some((Some(i), _)) if i == layer))
+            .collect::<Vec<_>>();
+
+        let mut res = 0.;
+
+        for c0 in keys.iter()
+        {
+            for c1 in keys.iter()
+            {
+                res += language_data
+                    .bigrams
+                    .get(&format!("{}{}", c0, c1))
+                    .unwrap_or(&0.);
+            }
+        }
+
+        return res;
+    }
+
+    pub fn total_activation(&self) -> f32
+    {
+        return self.activation.values().sum();
+    }
+}
+
+impl Display for LayerStats
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result
+    {
+        let mut format = "Layers:\n".to_string();
+
+        self.usage.iter().for_each(|(key, value)| {
+            let s = match self.activation.get(key)
+            {
+                | None => format!("  {:11} {:.3}%\n", key, *value),
+                | Some(a) => format!("  {:11} {:.3}% (activation {:.3}%)\n", key, *value, *a),
+            };
+
+            format.push_str(s.as_str());
+        });
+
+        write!(f, "{}", format.clone())
+    }
+}
+
+#[test]
+fn layer_stats()
+{
+    use crate::hand::finger::Finger::RT;
+    use crate::layout::layer::Activation::OneShot;
+    use crate::layout::layer::Layer;
+    use crate::testing::assert_close;
+
+    let language_data = LanguageData::test()
+        .with_characters(&[('a', 0.5), ('-', 0.3), ('=', 0.2)])
+        .with_bigrams(&[("-=", 0.1), ("a-", 0.2)]);
+
+    let mut layer = Layer::new("sym", Hold, RT);
+    layer.matrix[0] = '-';
+    layer.matrix[1] = '=';
+
+    let mut layout = Layout::default();
+    layout.matrix[0] = 'a';
+    layout.layers.push(layer);
+
+    let stats = LayerStats::new(&language_data, &layout);
+
+    assert_close(stats.usage[BASE_LAYER], 50.);
+    assert_close(stats.usage["sym"], 50.);
+    // `-=` is typed without letting go of the layer key.
+    assert_close(stats.activation["sym"], 40.);
+
+    layout.layers[0].activation = OneShot;
+
+    assert_close(LayerStats::new(&language_data, &layout).total_activation(), 50.);
+
+    // `a` is typed from the base layer, so `a-` and `-a` never hold the layer key.
+    layout.layers[0].activation = Hold;
+    layout.layers[0].matrix[2] = 'a';
+
+    let language_data = language_data.with_bigrams(&[("-a", 0.3), ("a-", 0.2), ("aa", 0.1)]);
+    let stats = LayerStats::new(&language_data, &layout);
+
+    assert_close(stats.usage[BASE_LAYER], 50.);
+    assert_close(stats.activation["sym"], 50.);
+}
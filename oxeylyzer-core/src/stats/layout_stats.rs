@@ -1,68 +1,17 @@
-use crate::language_data::LanguageData;
+use crate::indexed_data::IndexedData;
 use crate::layout::layout::Layout;
-use crate::stats::bigram_stats::BType::{
-    Repeat,
-    AB,
-    IRB,
-    LSB,
-    ORB,
-    S,
-    SFB,
-};
 use crate::stats::bigram_stats::{
     BType,
     BigramStats,
 };
-use crate::stats::disjoint_stats::DType::{
-    D1Repeat,
-    D1IRB,
-    D1LSB,
-    D1ORB,
-    D1S,
-    D1SFB,
-};
 use crate::stats::disjoint_stats::{
     D1Stats,
     DType,
 };
-use crate::stats::skip_stats::S1Type::{
-    S1Repeat,
-    S1IRB,
-    S1LSB,
-    S1ORB,
-    S1S,
-    S1SFB,
-};
-use crate::stats::skip_stats::S2Type::{
-    S2Repeat,
-    S2IRB,
-    S2LSB,
-    S2ORB,
-    S2S,
-    S2SFB,
-};
-use crate::stats::skip_stats::S3Type::{
-    S3Repeat,
-    S3IRB,
-    S3LSB,
-    S3ORB,
-    S3S,
-    S3SFB,
-};
+use crate::stats::layer_stats::LayerStats;
 use crate::stats::skip_stats::{
-    S1Stats,
-    S1Type,
-    S2Stats,
-    S2Type,
-    S3Stats,
-    S3Type,
-};
-use crate::stats::trigram_stats::TType::{
-    Redirect,
-    AT,
-    IRT,
-    ORT,
-    SFT,
+    SkipStats,
+    SkipType,
 };
 use crate::stats::trigram_stats::{
     TStats,
@@ -77,9 +26,10 @@ pub struct LayoutStats
     pub bigram_stats: BigramStats,
     pub trigram_stats: TStats,
     pub disjoint_stats: D1Stats,
-    pub skip1_stats: S1Stats,
-    pub skip2_stats: S2Stats,
-    pub skip3_stats: S3Stats,
+    pub skip1_stats: SkipStats<1>,
+    pub skip2_stats: SkipStats<2>,
+    pub skip3_stats: SkipStats<3>,
+    pub layer_stats: LayerStats,
 }
 
 impl Index<BType> for LayoutStats
@@ -113,70 +63,64 @@ impl Index<DType> for LayoutStats
 
 impl LayoutStats
 {
-    pub fn new(language_data: &LanguageData, layout: &Layout) -> Self
+    pub fn new(data: &IndexedData, layout: &Layout) -> Self
     {
-        let b = [SFB, LSB, IRB, ORB, AB, Repeat, S];
-        let t = [SFT, IRT, ORT, Redirect, AT];
-        let d = [D1SFB, D1LSB, D1IRB, D1ORB, D1Repeat, D1S];
-        let s1 = [S1SFB, S1LSB, S1IRB, S1ORB, S1Repeat, S1S];
-        let s2 = [S2SFB, S2LSB, S2IRB, S2ORB, S2Repeat, S2S];
-        let s3 = [S3SFB, S3LSB, S3IRB, S3ORB, S3Repeat, S3S];
-
         return Self {
-            bigram_stats: BigramStats::new(language_data, &layout.matrix, &b),
-            trigram_stats: TStats::new(language_data, &layout.matrix, &t),
-            disjoint_stats: D1Stats::new(language_data, &layout.matrix, &d),
-            skip1_stats: S1Stats::new(language_data, &layout.matrix, &s1),
-            skip2_stats: S2Stats::new(language_data, &layout.matrix, &s2),
-            skip3_stats: S3Stats::new(language_data, &layout.matrix, &s3),
+            bigram_stats: BigramStats::new(data, &layout.matrix, &BType::ALL),
+            trigram_stats: TStats::new(data, &layout.matrix, &TType::ALL),
+            disjoint_stats: D1Stats::new(data, &layout.matrix, &DType::ALL),
+            skip1_stats: SkipStats::new(data, &layout.matrix, &SkipType::ALL),
+            skip2_stats: SkipStats::new(data, &layout.matrix, &SkipType::ALL),
+            skip3_stats: SkipStats::new(data, &layout.matrix, &SkipType::ALL),
+            layer_stats: LayerStats::new(&data.language_data, layout),
         };
     }
 
     pub fn with(
-        language_data: &LanguageData,
+        data: &IndexedData,
         layout: &Layout,
         b: Option<&[BType]>,
         t: Option<&[TType]>,
         d: Option<&[DType]>,
-        s1: Option<&[S1Type]>,
-        s2: Option<&[S2Type]>,
-        s3: Option<&[S3Type]>,
+        s1: Option<&[SkipType]>,
+        s2: Option<&[SkipType]>,
+        s3: Option<&[SkipType]>,
     ) -> Self
     {
         let bigram_stats = match b
         {
             | None => BigramStats::default(),
-            | Some(s) => BigramStats::new(language_data, &layout.matrix, s),
+            | Some(s) => BigramStats::new(data, &layout.matrix, s),
         };
 
         let trigram_stats = match t
         {
             | None => TStats::default(),
-            | Some(s) => TStats::new(language_data, &layout.matrix, s),
+            | Some(s) => TStats::new(data, &layout.matrix, s),
         };
 
         let disjoint_stats = match d
         {
             | None => D1Stats::default(),
-            | Some(s) => D1Stats::new(language_data, &layout.matrix, s),
+            | Some(s) => D1Stats::new(data, &layout.matrix, s),
         };
 
         let skip1_stats = match s1
         {
-            | None => S1Stats::default(),
-            | Some(s) => S1Stats::new(language_data, &layout.matrix, s),
+            | None => SkipStats::default(),
+            | Some(s) => SkipStats::new(data, &layout.matrix, s),
         };
 
         let skip2_stats = match s2
         {
-            | None => S2Stats::default(),
-            | Some(s) => S2Stats::new(language_data, &layout.matrix, s),
+            | None => SkipStats::default(),
+            | Some(s) => SkipStats::new(data, &layout.matrix, s),
         };
 
         let skip3_stats = match s3
         {
-            | None => S3Stats::default(),
-            | Some(s) => S3Stats::new(language_data, &layout.matrix, s),
+            | None => SkipStats::default(),
+            | Some(s) => SkipStats::new(data, &layout.matrix, s),
         };
 
         return Self {
@@ -186,6 +130,7 @@ impl LayoutStats
             skip1_stats,
             skip2_stats,
             skip3_stats,
+            layer_stats: LayerStats::new(&data.language_data, layout),
         };
     }
 
@@ -331,6 +276,31 @@ impl LayoutStats
         return false;
     }
 
+    /// A scissor where either key is pressed by a pinky or a ring finger. These are the ones
+    /// `bad` in `[bigrams.scissors]` is for.
+    #[inline]
+    pub fn is_pinky_ring_scissor(a: &mut [u8]) -> bool
+    {
+        let bad = a.iter().any(|x| Self::is_bad_column(x % 10));
+
+        return bad && Self::is_scissor(a);
+    }
+
+    /// A same finger n-gram on a pinky or a ring finger.
+    #[inline]
+    pub fn is_bad_sf(a: &mut [u8]) -> bool
+    {
+        let bad = Self::is_bad_column(a[0] % 10);
+
+        return bad && Self::is_sf(a);
+    }
+
+    #[inline(always)]
+    const fn is_bad_column(column: u8) -> bool
+    {
+        return matches!(column, 0 | 1 | 8 | 9);
+    }
+
     #[inline]
     pub fn is_lsb(a: &mut [u8]) -> bool
     {
@@ -353,8 +323,23 @@ impl LayoutStats
         return (max == 4 && (min <= 2)) || (min == 5 && (max >= 7));
     }
 
+    /// Keys on one hand, each on a finger closer to the index finger than the one before, e.g.
+    /// pinky to middle on either hand. This is what in rolls mean in every stat.
     #[inline]
     pub fn is_inroll(a: &mut [u8]) -> bool
+    {
+        return Self::is_roll(a, true);
+    }
+
+    /// Keys on one hand, each on a finger further from the index finger than the one before.
+    #[inline]
+    pub fn is_outroll(a: &mut [u8]) -> bool
+    {
+        return Self::is_roll(a, false);
+    }
+
+    #[inline]
+    fn is_roll(a: &mut [u8], inward: bool) -> bool
     {
         if !Self::unique(a)
         {
@@ -363,20 +348,38 @@ impl LayoutStats
 
         Self::mod_all(a);
 
-        return a.windows(2).all(|x| x[0] <= x[1]);
-    }
+        let left = a[0] <= 4;
 
-    #[inline]
-    pub fn is_outroll(a: &mut [u8]) -> bool
-    {
-        if !Self::unique(a)
+        if !a.iter().all(|x| (*x <= 4) == left)
         {
             return false;
         }
 
-        Self::mod_all(a);
+        return a.windows(2).all(|x| {
+            let (f0, f1) = (Self::column_finger(x[0]), Self::column_finger(x[1]));
+
+            // Towards the index finger is to the right on the left hand, and left on the right.
+            f0 != f1 && ((f0 < f1) == left) == inward
+        });
+    }
+
+    /// The finger of a column, numbered like `Finger`. Index fingers have two columns.
+    #[inline(always)]
+    const fn column_finger(column: u8) -> u8
+    {
+        return match column
+        {
+            | 4 => 3,
+            | 5 => 6,
+            | c => c,
+        };
+    }
 
-        return a.windows(2).all(|x| x[0] >= x[1]);
+    /// Whether a column is typed with an index finger.
+    #[inline(always)]
+    pub(crate) const fn is_index_column(column: u8) -> bool
+    {
+        return matches!(column, 3 ..= 6);
     }
 
     #[inline]
@@ -473,3 +476,32 @@ pub trait Stats
     fn geometric_mean(&self) -> f32;
     fn root_square_mean(&self) -> f32;
 }
+
+#[test]
+fn scissors()
+{
+    // Top left pinky to bottom ring, and top middle to bottom index.
+    assert!(LayoutStats::is_scissor(&mut [0, 21]));
+    assert!(LayoutStats::is_scissor(&mut [2, 23]));
+
+    assert!(LayoutStats::is_pinky_ring_scissor(&mut [0, 21]));
+    assert!(!LayoutStats::is_pinky_ring_scissor(&mut [2, 23]));
+
+    assert!(LayoutStats::is_bad_scissor(&mut [2, 23]));
+    assert!(!LayoutStats::is_scissor(&mut [2, 22]));
+}
+
+#[test]
+fn rolls()
+{
+    // Pinky to middle goes in on both hands, ring to pinky goes out.
+    assert!(LayoutStats::is_inroll(&mut [10, 12]));
+    assert!(LayoutStats::is_inroll(&mut [19, 17]));
+    assert!(LayoutStats::is_outroll(&mut [11, 10]));
+    assert!(LayoutStats::is_outroll(&mut [18, 19]));
+
+    // Same finger, and across hands.
+    assert!(!LayoutStats::is_inroll(&mut [13, 14]));
+    assert!(!LayoutStats::is_inroll(&mut [10, 17]));
+    assert!(!LayoutStats::is_outroll(&mut [17, 10]));
+}
@@ -1,5 +1,11 @@
 pub mod bigram_stats;
+pub mod breakdown;
 pub mod disjoint_stats;
+pub mod fspeed_stats;
+pub mod heatmap_stats;
+pub mod layer_stats;
 pub mod layout_stats;
-mod skip_stats;
+pub mod patterns;
+pub mod skip_stats;
 pub mod trigram_stats;
+pub mod usage_stats;
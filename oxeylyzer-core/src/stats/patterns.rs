@@ -0,0 +1,211 @@
+use crate::stats::layout_stats::LayoutStats;
+use crate::stats::trigram_stats::TType;
+use crate::type_def::Fixed;
+use itertools::Itertools;
+use once_cell::sync::Lazy;
+
+/// Number of keys on a layout.
+pub const KEYS: usize = 30;
+
+/// The patterns of every bigram and trigram of positions. They only depend on where the
+/// positions are in the matrix, so they're built once and shared by all layouts.
+pub static PATTERNS: Lazy<Patterns> = Lazy::new(Patterns::new);
+
+/// What a pair of positions can be. A pair can be several of these at once.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+pub enum BigramPattern
+{
+    SFB,
+    /// A same finger bigram on a pinky or a ring finger.
+    BadSFB,
+    LSB,
+    IRB,
+    ORB,
+    AB,
+    Repeat,
+    S,
+    /// A scissor with a pinky or a ring finger.
+    BadS,
+}
+
+impl BigramPattern
+{
+    pub const ALL: [BigramPattern; 9] = [
+        BigramPattern::SFB,
+        BigramPattern::BadSFB,
+        BigramPattern::LSB,
+        BigramPattern::IRB,
+        BigramPattern::ORB,
+        BigramPattern::AB,
+        BigramPattern::Repeat,
+        BigramPattern::S,
+        BigramPattern::BadS,
+    ];
+
+    fn f(&self) -> fn(a: &mut [u8]) -> bool
+    {
+        return match self
+        {
+            | BigramPattern::SFB => LayoutStats::is_sf,
+            | BigramPattern::BadSFB => LayoutStats::is_bad_sf,
+            | BigramPattern::LSB => LayoutStats::is_lsb,
+            | BigramPattern::IRB => LayoutStats::is_inroll,
+            | BigramPattern::ORB => LayoutStats::is_outroll,
+            | BigramPattern::AB => LayoutStats::is_alternate,
+            | BigramPattern::Repeat => LayoutStats::is_repeat,
+            | BigramPattern::S => LayoutStats::is_scissor,
+            | BigramPattern::BadS => LayoutStats::is_pinky_ring_scissor,
+        };
+    }
+
+    const fn bit(&self) -> u16
+    {
+        return 1 << *self as u16;
+    }
+}
+
+pub struct Patterns
+{
+    /// The bigram patterns of every pair of positions as bits, indexed by `a * KEYS + b`.
+    bigrams: Vec<u16>,
+    /// The trigram type of every three positions, indexed by `(a * KEYS + b) * KEYS + c`.
+    trigrams: Vec<TType>,
+}
+
+impl Patterns
+{
+    pub fn new() -> Self
+    {
+        let bigrams = (0 .. KEYS)
+            .cartesian_product(0 .. KEYS)
+            .map(|(a, b)| {
+                BigramPattern::ALL
+                    .iter()
+                    .filter(|p| p.f()(&mut [a as u8, b as u8]))
+                    .fold(0, |bits, p| bits | p.bit())
+            })
+            .collect_vec();
+
+        let trigrams = (0 .. KEYS)
+            .cartesian_product(0 .. KEYS)
+            .cartesian_product(0 .. KEYS)
+            .map(|((a, b), c)| TType::of([a, b, c]))
+            .collect_vec();
+
+        return Self { bigrams, trigrams };
+    }
+
+    #[inline(always)]
+    pub fn is_bigram(&self, a: usize, b: usize, pattern: BigramPattern) -> bool
+    {
+        return self.bigrams[a * KEYS + b] & pattern.bit() != 0;
+    }
+
+    #[inline(always)]
+    pub fn trigram(&self, a: usize, b: usize, c: usize) -> TType
+    {
+        return self.trigrams[(a * KEYS + b) * KEYS + c];
+    }
+
+    /// The position of every character on a layout.
+    pub fn positions(chars: &Fixed<char>) -> CharIndex
+    {
+        return CharIndex::new(chars);
+    }
+
+    /// The positions of the characters of an n-gram, if they are all on the layout.
+    #[inline]
+    pub fn locate<const N: usize>(positions: &CharIndex, ngram: &str) -> Option<[usize; N]>
+    {
+        let mut keys = [0; N];
+        let mut chars = ngram.chars();
+
+        for key in keys.iter_mut()
+        {
+            *key = positions.get(chars.next()?)?;
+        }
+
+        return Some(keys);
+    }
+}
+
+/// Maps the characters of a layout to their positions. ASCII characters are looked up in a
+/// table, the few others by going through them.
+#[derive(Clone, Debug)]
+pub struct CharIndex
+{
+    ascii: [u8; 128],
+    other: Vec<(char, u8)>,
+}
+
+impl CharIndex
+{
+    const NONE: u8 = u8::MAX;
+
+    /// A character that is on the layout twice gets the first position.
+    pub fn new(chars: &Fixed<char>) -> Self
+    {
+        let mut ascii = [Self::NONE; 128];
+        let mut other = Vec::new();
+
+        for (i, c) in chars.iter().enumerate().rev()
+        {
+            match c.is_ascii()
+            {
+                | true => ascii[*c as usize] = i as u8,
+                | false => other.insert(0, (*c, i as u8)),
+            }
+        }
+
+        return Self { ascii, other };
+    }
+
+    #[inline(always)]
+    pub fn get(&self, c: char) -> Option<usize>
+    {
+        let i = match c.is_ascii()
+        {
+            | true => self.ascii[c as usize],
+            | false =>
+            {
+                self.other
+                    .iter()
+                    .find(|(o, _)| *o == c)
+                    .map_or(Self::NONE, |(_, i)| *i)
+            },
+        };
+
+        return if i == Self::NONE { None } else { Some(i as usize) };
+    }
+
+    pub fn remove(&mut self, c: char)
+    {
+        match c.is_ascii()
+        {
+            | true => self.ascii[c as usize] = Self::NONE,
+            | false => self.other.retain(|(o, _)| *o != c),
+        }
+    }
+}
+
+impl Default for Patterns
+{
+    fn default() -> Self
+    {
+        return Self::new();
+    }
+}
+
+#[test]
+fn patterns()
+{
+    for (a, b) in (0 .. KEYS).cartesian_product(0 .. KEYS)
+    {
+        for p in BigramPattern::ALL
+        {
+            assert_eq!(PATTERNS.is_bigram(a, b, p), p.f()(&mut [a as u8, b as u8]));
+        }
+    }
+
+    assert_eq!(PATTERNS.trigram(11, 10, 18), TType::of([11, 10, 18]));
+}
@@ -1,12 +1,9 @@
-use crate::language_data::LanguageData;
-use crate::stats::layout_stats::LayoutStats;
-use crate::stats::skip_stats::S1Type::*;
-use crate::stats::skip_stats::S2Type::*;
-use crate::stats::skip_stats::S3Type::*;
+use crate::indexed_data::IndexedData;
+use crate::stats::bigram_stats::BigramStats;
+use crate::stats::patterns::BigramPattern;
+use crate::stats::skip_stats::SkipType::*;
 use crate::type_def::Fixed;
-use indexmap::map::Entry;
 use indexmap::IndexMap;
-use std::collections::HashMap;
 use std::fmt;
 use std::fmt::{
     Display,
@@ -15,295 +12,83 @@ use std::fmt::{
 use std::ops::Index;
 
 #[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
-pub enum S1Type
+pub enum SkipType
 {
-    S1SFB,
-    S1LSB,
-    S1IRB,
-    S1ORB,
-    S1Repeat,
-    S1S,
+    SFB,
+    LSB,
+    IRB,
+    ORB,
+    Repeat,
+    S,
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
-pub enum S2Type
-{
-    S2SFB,
-    S2LSB,
-    S2IRB,
-    S2ORB,
-    S2Repeat,
-    S2S,
-}
-
-#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
-pub enum S3Type
+impl SkipType
 {
-    S3SFB,
-    S3LSB,
-    S3IRB,
-    S3ORB,
-    S3Repeat,
-    S3S,
-}
-
-impl S1Type
-{
-    fn f(&self) -> fn(a: &mut [u8]) -> bool
-    {
-        return match self
-        {
-            | S1SFB => LayoutStats::is_sf,
-            | S1LSB => LayoutStats::is_lsb,
-            | S1IRB => LayoutStats::is_inroll,
-            | S1ORB => LayoutStats::is_outroll,
-            | S1Repeat => LayoutStats::is_repeat,
-            | S1S => LayoutStats::is_scissor,
-        };
-    }
-}
-
-impl S2Type
-{
-    fn f(&self) -> fn(a: &mut [u8]) -> bool
-    {
-        return match self
-        {
-            | S2SFB => LayoutStats::is_sf,
-            | S2LSB => LayoutStats::is_lsb,
-            | S2IRB => LayoutStats::is_inroll,
-            | S2ORB => LayoutStats::is_outroll,
-            | S2Repeat => LayoutStats::is_repeat,
-            | S2S => LayoutStats::is_scissor,
-        };
-    }
-}
+    pub const ALL: [SkipType; 6] = [SFB, LSB, IRB, ORB, Repeat, S];
 
-impl S3Type
-{
-    fn f(&self) -> fn(a: &mut [u8]) -> bool
+    pub(crate) const fn pattern(&self) -> BigramPattern
     {
         return match self
         {
-            | S3SFB => LayoutStats::is_sf,
-            | S3LSB => LayoutStats::is_lsb,
-            | S3IRB => LayoutStats::is_inroll,
-            | S3ORB => LayoutStats::is_outroll,
-            | S3Repeat => LayoutStats::is_repeat,
-            | S3S => LayoutStats::is_scissor,
+            | SFB => BigramPattern::SFB,
+            | LSB => BigramPattern::LSB,
+            | IRB => BigramPattern::IRB,
+            | ORB => BigramPattern::ORB,
+            | Repeat => BigramPattern::Repeat,
+            | S => BigramPattern::S,
         };
     }
 }
 
+/// Bigram stats of skipgrams with `N` keys in between, read from the matching skipgram table of
+/// the language data. They are all 0 when the data has no skipgrams that far apart.
 #[derive(Default, Clone)]
-pub struct S1Stats
+pub struct SkipStats<const N: usize>
 {
-    pub inner: IndexMap<S1Type, f32>,
+    pub inner: IndexMap<SkipType, f32>,
 }
 
-impl S1Stats
+impl<const N: usize> SkipStats<N>
 {
-    pub fn new(language_data: &LanguageData, chars: &Fixed<char>, a: &[S1Type]) -> Self
+    pub fn new(data: &IndexedData, chars: &Fixed<char>, a: &[SkipType]) -> Self
     {
-        let mut stats = IndexMap::new();
-
-        Self::p2(chars, &language_data.trigrams, &mut stats, a);
-
-        return Self { inner: stats };
-    }
-
-    pub(crate) fn p2(
-        chars: &Fixed<char>,
-        data: &HashMap<String, f32>,
-        index_map: &mut IndexMap<S1Type, f32>,
-        a: &[S1Type],
-    )
-    {
-        for i in 0 .. 30
-        {
-            let i_left = LayoutStats::is_left_hand(&i);
-
-            for j in 0 .. 30
-            {
-                let j_left = LayoutStats::is_left_hand(&j);
-
-                if i_left == j_left
-                {
-                    continue;
-                }
+        let keys = data.keys(chars);
 
-                for t in a
-                {
-                    for k in 0 .. 30
-                    {
-                        if t.f()(&mut [i as u8, k as u8])
-                        {
-                            let k_left = LayoutStats::is_left_hand(&k);
-
-                            if j_left == k_left
-                            {
-                                continue;
-                            }
-
-                            let c0 = chars[i];
-                            let c1 = chars[j];
-                            let c2 = chars[k];
-
-                            if [c0, c1, c2].iter().any(char::is_ascii_punctuation)
-                            {
-                                continue;
-                            }
-
-                            let p = data.get(&format!("{}{}{}", c0, c1, c2)).unwrap_or(&0.0);
-
-                            match index_map.entry(*t)
-                            {
-                                | Entry::Occupied(mut e) =>
-                                {
-                                    *e.get_mut() += p;
-                                },
-                                | Entry::Vacant(e) =>
-                                {
-                                    *e.insert(*p);
-                                },
-                            }
-                        }
-                    }
-                }
-            }
-        }
-
-        index_map.values_mut().for_each(|mut x| *x *= 100.);
-    }
-}
-
-impl Index<S1Type> for S1Stats
-{
-    type Output = f32;
-
-    fn index(&self, index: S1Type) -> &Self::Output
-    {
-        return &self.inner[&index];
-    }
-}
-
-impl Display for S1Stats
-{
-    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result
-    {
-        let mut format = "Disjoints:\n".to_string();
-
-        self.inner.iter().for_each(|(key, value)| {
-            let k = format!("{:?}", key);
-            let s = format!("  {:11} {:.3}%\n", k, *value);
-
-            format.push_str(s.as_str());
-        });
-
-        write!(f, "{}", format.clone())
-    }
-}
-
-#[derive(Default, Clone)]
-pub struct S2Stats
-{
-    pub inner: IndexMap<S2Type, f32>,
-}
-
-impl S2Stats
-{
-    pub fn new(language_data: &LanguageData, chars: &Fixed<char>, a: &[S2Type]) -> Self
-    {
         let mut stats = IndexMap::new();
 
-        Self::p2(chars, &language_data.trigrams, &mut stats, a);
-
-        return Self { inner: stats };
-    }
-
-    pub(crate) fn p2(
-        chars: &Fixed<char>,
-        data: &HashMap<String, f32>,
-        index_map: &mut IndexMap<S2Type, f32>,
-        a: &[S2Type],
-    )
-    {
-        for i in 0 .. 30
+        for t in a
         {
-            let i_left = LayoutStats::is_left_hand(&i);
-
-            for j in 0 .. 30
+            let p = match data.skipgrams_at(N)
             {
-                let j_left = LayoutStats::is_left_hand(&j);
-
-                if i_left == j_left
-                {
-                    continue;
-                }
-
-                for t in a
-                {
-                    for k in 0 .. 30
-                    {
-                        if t.f()(&mut [i as u8, k as u8])
-                        {
-                            let k_left = LayoutStats::is_left_hand(&k);
-
-                            if j_left == k_left
-                            {
-                                continue;
-                            }
-
-                            let c0 = chars[i];
-                            let c1 = chars[j];
-                            let c2 = chars[k];
-
-                            if [c0, c1, c2].iter().any(char::is_ascii_punctuation)
-                            {
-                                continue;
-                            }
-
-                            let p = data.get(&format!("{}{}{}", c0, c1, c2)).unwrap_or(&0.0);
+                | None => 0.,
+                | Some(skipgrams) => BigramStats::p1(chars, &keys, skipgrams, t.pattern()),
+            };
 
-                            match index_map.entry(*t)
-                            {
-                                | Entry::Occupied(mut e) =>
-                                {
-                                    *e.get_mut() += p;
-                                },
-                                | Entry::Vacant(e) =>
-                                {
-                                    *e.insert(*p);
-                                },
-                            }
-                        }
-                    }
-                }
-            }
+            stats.insert(*t, p);
         }
 
-        index_map.values_mut().for_each(|mut x| *x *= 100.);
+        return Self { inner: stats };
     }
 }
 
-impl Index<S2Type> for S2Stats
+impl<const N: usize> Index<SkipType> for SkipStats<N>
 {
     type Output = f32;
 
-    fn index(&self, index: S2Type) -> &Self::Output
+    fn index(&self, index: SkipType) -> &Self::Output
     {
         return &self.inner[&index];
     }
 }
 
-impl Display for S2Stats
+impl<const N: usize> Display for SkipStats<N>
 {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result
     {
-        let mut format = "Disjoints:\n".to_string();
+        let mut format = format!("Skipgrams ({N} between):\n");
 
         self.inner.iter().for_each(|(key, value)| {
-            let k = format!("{:?}", key);
+            let k = format!("S{N}{:?}", key);
             let s = format!("  {:11} {:.3}%\n", k, *value);
 
             format.push_str(s.as_str());
@@ -313,111 +98,34 @@ impl Display for S2Stats
     }
 }
 
-#[derive(Default, Clone)]
-pub struct S3Stats
-{
-    pub inner: IndexMap<S3Type, f32>,
-}
-
-impl S3Stats
-{
-    pub fn new(language_data: &LanguageData, chars: &Fixed<char>, a: &[S3Type]) -> Self
-    {
-        let mut stats = IndexMap::new();
-
-        Self::p2(chars, &language_data.trigrams, &mut stats, a);
-
-        return Self { inner: stats };
-    }
-
-    pub(crate) fn p2(
-        chars: &Fixed<char>,
-        data: &HashMap<String, f32>,
-        index_map: &mut IndexMap<S3Type, f32>,
-        a: &[S3Type],
-    )
-    {
-        for i in 0 .. 30
-        {
-            let i_left = LayoutStats::is_left_hand(&i);
-
-            for j in 0 .. 30
-            {
-                let j_left = LayoutStats::is_left_hand(&j);
-
-                if i_left == j_left
-                {
-                    continue;
-                }
-
-                for t in a
-                {
-                    for k in 0 .. 30
-                    {
-                        if t.f()(&mut [i as u8, k as u8])
-                        {
-                            let k_left = LayoutStats::is_left_hand(&k);
-
-                            if j_left == k_left
-                            {
-                                continue;
-                            }
-
-                            let c0 = chars[i];
-                            let c1 = chars[j];
-                            let c2 = chars[k];
-
-                            if [c0, c1, c2].iter().any(char::is_ascii_punctuation)
-                            {
-                                continue;
-                            }
-
-                            let p = data.get(&format!("{}{}{}", c0, c1, c2)).unwrap_or(&0.0);
-
-                            match index_map.entry(*t)
-                            {
-                                | Entry::Occupied(mut e) =>
-                                {
-                                    *e.get_mut() += p;
-                                },
-                                | Entry::Vacant(e) =>
-                                {
-                                    *e.insert(*p);
-                                },
-                            }
-                        }
-                    }
-                }
-            }
-        }
-
-        index_map.values_mut().for_each(|mut x| *x *= 100.);
-    }
-}
-
-impl Index<S3Type> for S3Stats
-{
-    type Output = f32;
-
-    fn index(&self, index: S3Type) -> &Self::Output
-    {
-        return &self.inner[&index];
-    }
-}
-
-impl Display for S3Stats
-{
-    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result
-    {
-        let mut format = "Disjoints:\n".to_string();
-
-        self.inner.iter().for_each(|(key, value)| {
-            let k = format!("{:?}", key);
-            let s = format!("  {:11} {:.3}%\n", k, *value);
-
-            format.push_str(s.as_str());
-        });
-
-        write!(f, "{}", format.clone())
-    }
+#[test]
+fn skip_stats()
+{
+    use crate::language_data::LanguageData;
+    use crate::testing::assert_close;
+    use itertools::Itertools;
+
+    // `ak` is a same finger bigram. If the skipgram stats read the trigrams, `axk` would make
+    // every one of them 100%.
+    let language_data = LanguageData::test()
+        .with_bigrams(&[("ak", 0.5)])
+        .with_skipgrams(1, &[("ak", 0.1), ("ab", 0.9)])
+        .with_skipgrams(2, &[("ak", 0.2), ("ab", 0.8)])
+        .with_skipgrams(3, &[("ak", 0.3), ("ab", 0.7)])
+        .with_skipgrams(4, &[("ak", 0.4), ("ab", 0.6)])
+        .with_trigrams(&[("axk", 1.0)]);
+
+    let chars: Fixed<char> = "abcdefghijklmnopqrstuvwxyz',.;"
+        .chars()
+        .collect_vec()
+        .try_into()
+        .unwrap();
+
+    let data = IndexedData::new(language_data, chars);
+
+    assert_close(SkipStats::<1>::new(&data, &chars, &[SFB])[SFB], 10.);
+    assert_close(SkipStats::<2>::new(&data, &chars, &[SFB])[SFB], 20.);
+    assert_close(SkipStats::<3>::new(&data, &chars, &[SFB])[SFB], 30.);
+    assert_close(SkipStats::<4>::new(&data, &chars, &[SFB])[SFB], 40.);
+    assert_close(SkipStats::<5>::new(&data, &chars, &[SFB])[SFB], 0.);
 }
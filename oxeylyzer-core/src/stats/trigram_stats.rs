@@ -1,10 +1,15 @@
-use crate::language_data::LanguageData;
+use crate::indexed_data::IndexedData;
 use crate::stats::layout_stats::LayoutStats;
+use crate::stats::patterns::{
+    CharIndex,
+    Patterns,
+    KEYS,
+    PATTERNS,
+};
 use crate::stats::trigram_stats::TType::*;
 use crate::type_def::Fixed;
-use indexmap::map::Entry;
 use indexmap::IndexMap;
-use std::collections::HashMap;
+use itertools::Itertools;
 use std::fmt;
 use std::fmt::{
     Display,
@@ -12,27 +17,106 @@ use std::fmt::{
 };
 use std::ops::Index;
 
+/// What kind of trigram a trigram is on a layout. Every trigram is exactly one of these.
 #[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
 pub enum TType
 {
+    /// All three keys on the same finger.
     SFT,
+    /// A same finger bigram in the first or last two keys.
+    SFBT,
+    /// An in roll, see `LayoutStats::is_inroll`, and a key on the other hand.
     IRT,
+    /// An out roll, see `LayoutStats::is_outroll`, and a key on the other hand.
     ORT,
+    /// Three keys on one hand rolling in.
+    OneHandIn,
+    /// Three keys on one hand rolling out.
+    OneHandOut,
+    /// Three keys on one hand that change direction.
     Redirect,
+    /// A redirect where the first and last key are on the same finger.
+    RedirectSFS,
+    /// A redirect without an index finger.
+    BadRedirect,
+    /// A bad redirect where the first and last key are on the same finger.
+    BadRedirectSFS,
+    /// The first and last key on one hand, the middle one on the other.
     AT,
+    /// An alternate where the first and last key are on the same finger.
+    AltSFS,
+    /// Trigrams that repeat a key right away.
+    Other,
+    /// Trigrams with a character that isn't on the layout.
+    Invalid,
 }
 
 impl TType
 {
-    fn f(&self) -> fn(a: &mut [u8]) -> bool
+    pub const ALL: [TType; 14] = [
+        SFT,
+        SFBT,
+        IRT,
+        ORT,
+        OneHandIn,
+        OneHandOut,
+        Redirect,
+        RedirectSFS,
+        BadRedirect,
+        BadRedirectSFS,
+        AT,
+        AltSFS,
+        Other,
+        Invalid,
+    ];
+
+    /// The kind of trigram the keys at these positions make.
+    pub fn of(positions: [usize; 3]) -> Self
     {
-        return match self
+        let [a, b, c] = positions;
+
+        let left = |i: usize| LayoutStats::is_left_hand(&i);
+        let sf = |i: usize, j: usize| i == j || LayoutStats::is_sf(&mut [i as u8, j as u8]);
+        let inward = |i: usize, j: usize| LayoutStats::is_inroll(&mut [i as u8, j as u8]);
+
+        if a == b || b == c
+        {
+            return Other;
+        }
+
+        if sf(a, b) && sf(b, c)
+        {
+            return SFT;
+        }
+
+        if sf(a, b) || sf(b, c)
+        {
+            return SFBT;
+        }
+
+        return match (left(a) == left(b), left(b) == left(c))
         {
-            | SFT => LayoutStats::is_sf,
-            | IRT => LayoutStats::is_inroll,
-            | ORT => LayoutStats::is_outroll,
-            | Redirect => LayoutStats::is_redirect,
-            | AT => LayoutStats::is_inroll,
+            | (true, true) =>
+            {
+                let index = [a, b, c].iter().any(|i| LayoutStats::is_index_column((i % 10) as u8));
+                let sfs = sf(a, c);
+
+                match (inward(a, b), inward(b, c))
+                {
+                    | (true, true) => OneHandIn,
+                    | (false, false) => OneHandOut,
+                    | _ if index && sfs => RedirectSFS,
+                    | _ if index => Redirect,
+                    | _ if sfs => BadRedirectSFS,
+                    | _ => BadRedirect,
+                }
+            },
+            | (true, false) if inward(a, b) => IRT,
+            | (true, false) => ORT,
+            | (false, true) if inward(b, c) => IRT,
+            | (false, true) => ORT,
+            | (false, false) if sf(a, c) => AltSFS,
+            | (false, false) => AT,
         };
     }
 }
@@ -42,160 +126,43 @@ pub struct TStats
 {
     pub inner: IndexMap<TType, f32>,
 }
+
 impl TStats
 {
-    pub fn new(language_data: &LanguageData, chars: &Fixed<char>, a: &[TType]) -> Self
+    pub fn new(data: &IndexedData, chars: &Fixed<char>, a: &[TType]) -> Self
     {
-        let mut stats = IndexMap::new();
-
-        for t in a
-        {
-            stats.insert(*t, 0.);
-        }
+        let keys = data.keys(chars);
 
-        Self::p2(chars, &language_data.trigrams, &mut stats, a);
+        let mut totals = [0.; TType::ALL.len()];
 
-        return Self { inner: stats };
-    }
-
-    pub(crate) fn p2(
-        chars: &Fixed<char>,
-        data: &HashMap<String, f32>,
-        index_map: &mut IndexMap<TType, f32>,
-        a: &[TType],
-    )
-    {
-        for i in 0 .. 30
+        for ((i, j), k) in (0 .. KEYS).cartesian_product(0 .. KEYS).cartesian_product(0 .. KEYS)
         {
-            for j in 0 .. 30
+            if let (Some(ki), Some(kj), Some(kk)) = (keys[i], keys[j], keys[k])
             {
-                for k in 0 .. 30
-                {
-                    for t in a
-                    {
-                        if t.f()(&mut [i as u8, j as u8, k as u8])
-                        {
-                            let c0 = chars[i];
-                            let c1 = chars[j];
-                            let c2 = chars[k];
-
-                            if [c0, c1, c2].iter().any(char::is_ascii_punctuation)
-                            {
-                                continue;
-                            }
-
-                            let p = data.get(&format!("{}{}{}", c0, c1, c2)).unwrap_or(&0.0);
-
-                            match index_map.entry(*t)
-                            {
-                                | Entry::Occupied(mut e) =>
-                                {
-                                    *e.get_mut() += p;
-                                },
-                                | Entry::Vacant(_) =>
-                                {
-                                    panic!();
-                                },
-                            }
-                        }
-                    }
-                }
+                totals[PATTERNS.trigram(i, j, k) as usize] += data.trigram(ki, kj, kk);
             }
         }
 
-        index_map.values_mut().for_each(|mut x| *x *= 100.);
-    }
-
-    pub(crate) fn p1(
-        chars: &Fixed<char>,
-        data: &HashMap<String, f32>,
-        f: fn(&mut [u8]) -> bool,
-    ) -> f32
-    {
-        use rayon::iter::*;
-
-        let a = (0 .. 30).into_par_iter().map(|i| {
-            let b = (0 .. 30).into_par_iter().map(|j| {
-                let c = (0 .. 30).into_par_iter().map(|k| {
-                    if f(&mut [i as u8, j as u8, k as u8])
-                    {
-                        let c0 = chars[i];
-                        let c1 = chars[j];
-                        let c2 = chars[k];
-
-                        if c0 == c1 && c1 == c2
-                        {
-                            return 0.;
-                        }
+        // Whatever isn't on the layout.
+        totals[Invalid as usize] = data.trigram_total() - totals.iter().sum::<f32>();
 
-                        if [c0, c1, c2].iter().any(char::is_ascii_punctuation)
-                        {
-                            return 0.;
-                        }
+        let stats = a.iter().map(|t| (*t, totals[*t as usize] * 100.)).collect();
 
-                        let p = data.get(&format!("{}{}{}", c0, c1, c2)).unwrap_or(&0.);
-
-                        return *p;
-                    }
-
-                    return 0.;
-                });
-
-                return c.collect::<Vec<f32>>().iter().sum();
-            });
-
-            return b.collect::<Vec<f32>>().iter().sum();
-        });
-
-        let q: f32 = a.collect::<Vec<f32>>().iter().sum();
-
-        return q * 100.;
+        return Self { inner: stats };
     }
 
-    pub(crate) fn p(
-        chars: &Fixed<char>,
-        data: &HashMap<String, f32>,
-        f: fn(&mut [u8]) -> bool,
-    ) -> f32
+    /// The type of a trigram on the layout with these character positions.
+    #[inline]
+    pub fn classify(positions: &CharIndex, trigram: &str) -> TType
     {
-        let mut res = 0.;
-
-        for i in 0 .. 30
+        return match Patterns::locate(positions, trigram)
         {
-            for j in 0 .. 30
-            {
-                for k in 0 .. 30
-                {
-                    if f(&mut [i as u8, j as u8, k as u8])
-                    {
-                        let c0 = chars[i];
-                        let c1 = chars[j];
-                        let c2 = chars[k];
-
-                        if c0 == c1 && c1 == c2
-                        {
-                            continue;
-                        }
-
-                        if [c0, c1, c2].iter().any(char::is_ascii_punctuation)
-                        {
-                            continue;
-                        }
-
-                        let p = data.get(&format!("{}{}{}", c0, c1, c2)).unwrap_or(&0.0);
-
-                        res += p;
-                    }
-                }
-            }
-        }
-
-        return res * 100.;
+            | None => Invalid,
+            | Some([a, b, c]) => PATTERNS.trigram(a, b, c),
+        };
     }
 }
 
-impl TStats {}
-
 impl Index<TType> for TStats
 {
     type Output = f32;
@@ -214,7 +181,7 @@ impl Display for TStats
 
         self.inner.iter().for_each(|(key, value)| {
             let k = format!("{:?}", key);
-            let s = format!("  {:11} {:.3}%\n", k, *value);
+            let s = format!("  {:14} {:.3}%\n", k, *value);
 
             format.push_str(s.as_str());
         });
@@ -222,3 +189,55 @@ impl Display for TStats
         write!(f, "{}", format.clone())
     }
 }
+
+#[test]
+fn classify_trigrams()
+{
+    use crate::stats::patterns::BigramPattern;
+
+    // qwerty
+    let chars = "qwertyuiopasdfghjkl;zxcvbnm,./".chars().collect::<Vec<_>>();
+    let positions = Patterns::positions(&chars.try_into().unwrap());
+
+    let cases = [
+        ("ded", SFT),
+        ("sed", SFBT),
+        ("asl", IRT),
+        ("lkd", IRT),
+        ("fsj", ORT),
+        ("asd", OneHandIn),
+        ("lkj", OneHandIn),
+        ("dsa", OneHandOut),
+        ("era", Redirect),
+        ("fsv", RedirectSFS),
+        ("ads", BadRedirect),
+        ("saw", BadRedirectSFS),
+        ("ake", AT),
+        ("dhe", AltSFS),
+        ("ses", BadRedirectSFS),
+        ("sls", AltSFS),
+        ("see", Other),
+        ("s é", Invalid),
+    ];
+
+    for (trigram, t) in cases
+    {
+        assert_eq!(TStats::classify(&positions, trigram), t, "{trigram}");
+    }
+
+    // Rolls go the same way as the in and out roll bigrams on both hands.
+    for (a, b) in (0 .. KEYS).cartesian_product(0 .. KEYS)
+    {
+        let left = LayoutStats::is_left_hand(&a);
+
+        if left != LayoutStats::is_left_hand(&b)
+        {
+            continue;
+        }
+
+        let t = TType::of([a, b, if left { 19 } else { 10 }]);
+
+        assert_eq!(t == IRT, PATTERNS.is_bigram(a, b, BigramPattern::IRB), "{a} {b}");
+        assert_eq!(t == ORT, PATTERNS.is_bigram(a, b, BigramPattern::ORB), "{a} {b}");
+    }
+}
@@ -0,0 +1,169 @@
+use crate::config::finger_to_column::FingerToColumn;
+use crate::hand::finger::Finger;
+use crate::hand::finger::Finger::*;
+use crate::hand::hand::Hand;
+use crate::hand::hand::Hand::*;
+use crate::indexed_data::IndexedData;
+use crate::layout::layout::Layout;
+use indexmap::IndexMap;
+use std::fmt;
+use std::fmt::{
+    Display,
+    Formatter,
+};
+
+const ROWS: [&str; 3] = ["Top", "Home", "Bottom"];
+
+/// How much each finger, hand, row and column of a layout is used, as a share of all characters.
+#[derive(Default, Clone)]
+pub struct UsageStats
+{
+    pub fingers: IndexMap<Finger, f32>,
+    pub hands: IndexMap<Hand, f32>,
+    pub rows: [f32; 3],
+    pub columns: [f32; 10],
+}
+
+impl UsageStats
+{
+    pub fn new(data: &IndexedData, layout: &Layout, finger_to_column: &FingerToColumn) -> Self
+    {
+        let mut fingers = IndexMap::from_iter((0 .. 10).map(|f| (Finger::from(f), 0.)));
+        let mut hands = IndexMap::from_iter([(Left, 0.), (Right, 0.)]);
+        let mut rows = [0.; 3];
+        let mut columns = [0.; 10];
+
+        for (i, key) in data.keys(&layout.matrix).iter().enumerate()
+        {
+            let p = key.map_or(0., |k| data.characters[k]) * 100.;
+            let finger = finger_to_column[i];
+
+            fingers[&finger] += p;
+            hands[&finger.hand()] += p;
+            rows[i / 10] += p;
+            columns[i % 10] += p;
+        }
+
+        return Self {
+            fingers,
+            hands,
+            rows,
+            columns,
+        };
+    }
+
+    /// The usage of this layout and another one next to each other, with how much more the
+    /// other one uses everything.
+    pub fn compare(&self, other: &Self) -> String
+    {
+        let mut format = "Usage:\n".to_string();
+
+        for ((name, v0), (_, v1)) in self.values().into_iter().zip(other.values())
+        {
+            format.push_str(
+                format!("  {:6} {:>6.2}%  {:>6.2}%  {:>+6.2}%\n", name, v0, v1, v1 - v0).as_str(),
+            );
+        }
+
+        return format;
+    }
+
+    /// Every value with its name, hands first, then rows, fingers without the thumbs and columns.
+    fn values(&self) -> Vec<(String, f32)>
+    {
+        let hands = self.hands.iter().map(|(h, v)| (format!("{:?}", h), *v));
+        let rows = ROWS.iter().zip(self.rows).map(|(r, v)| (r.to_string(), v));
+
+        let fingers = self
+            .fingers
+            .iter()
+            .filter(|(f, _)| !matches!(f, LT | RT))
+            .map(|(f, v)| (format!("{:?}", f), *v));
+
+        let columns = self
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(c, v)| (format!("Col {}", c), *v));
+
+        return hands.chain(rows).chain(fingers).chain(columns).collect();
+    }
+}
+
+impl Display for UsageStats
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result
+    {
+        let mut format = "Usage:\n".to_string();
+
+        let name = |n: &dyn fmt::Debug| format!("{:?}", n);
+
+        // Two per line, the right one mirroring the left one. Thumbs have no keys on the layout,
+        // so they're left out.
+        let mut lines = vec![
+            ((name(&Left), self.hands[&Left]), Some((name(&Right), self.hands[&Right]))),
+            ((ROWS[0].to_string(), self.rows[0]), Some((ROWS[1].to_string(), self.rows[1]))),
+            ((ROWS[2].to_string(), self.rows[2]), None),
+        ];
+
+        for (l, r) in [(LP, RP), (LR, RR), (LM, RM), (LI, RI)]
+        {
+            lines.push(((name(&l), self.fingers[&l]), Some((name(&r), self.fingers[&r]))));
+        }
+
+        for c in 0 .. 5
+        {
+            let right = (format!("Col {}", 9 - c), self.columns[9 - c]);
+
+            lines.push(((format!("Col {}", c), self.columns[c]), Some(right)));
+        }
+
+        for ((k0, v0), right) in lines
+        {
+            let s = match right
+            {
+                | None => format!("  {:6} {:>5.2}%\n", k0, v0),
+                | Some((k1, v1)) => format!("  {:6} {:>5.2}%  {:6} {:>5.2}%\n", k0, v0, k1, v1),
+            };
+
+            format.push_str(s.as_str());
+        }
+
+        write!(f, "{}", format)
+    }
+}
+
+#[test]
+fn usage_stats()
+{
+    use crate::language_data::LanguageData;
+    use crate::testing::assert_close;
+    use itertools::Itertools;
+
+    // Every character of the corpus is on the layout.
+    let language_data = LanguageData::test().with_characters(&[
+        ('a', 0.3),
+        ('k', 0.1),
+        ('e', 0.25),
+        ('p', 0.15),
+        (';', 0.05),
+        ('x', 0.15),
+    ]);
+
+    let chars = "abcdefghijklmnopqrstuvwxyz',.;".chars().collect_vec();
+    let data = IndexedData::new(language_data, chars.clone().try_into().unwrap());
+    let layout = Layout::from(<[char; 30]>::try_from(chars).unwrap());
+
+    let usage = UsageStats::new(&data, &layout, &FingerToColumn::default());
+
+    assert_close(usage.fingers.values().sum(), 100.);
+    assert_close(usage.hands.values().sum(), 100.);
+    assert_close(usage.rows.iter().sum(), 100.);
+    assert_close(usage.columns.iter().sum(), 100.);
+
+    // `a` and `k` are on the left pinky, `;` on the right pinky.
+    assert_close(usage.fingers[&LP], 40.);
+    assert_close(usage.fingers[&RP], 5.);
+    assert_close(usage.hands[&Left], 80.);
+    assert_close(usage.rows[1], 25.);
+}
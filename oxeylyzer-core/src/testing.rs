@@ -0,0 +1,73 @@
+use crate::language_data::LanguageData;
+use std::collections::HashMap;
+
+/// Language data for tests, empty until n-grams are added with the methods below. Frequencies
+/// are fractions like in the corpus files, not percentages.
+impl LanguageData
+{
+    pub(crate) fn test() -> Self
+    {
+        return Self {
+            language: "test".to_string(),
+            characters: HashMap::new(),
+            bigrams: HashMap::new(),
+            skipgrams: HashMap::new(),
+            skipgrams2: HashMap::new(),
+            skipgrams3: HashMap::new(),
+            trigrams: HashMap::new(),
+            more_skipgrams: HashMap::new(),
+        };
+    }
+
+    pub(crate) fn with_characters(mut self, characters: &[(char, f32)]) -> Self
+    {
+        self.characters = characters.iter().copied().collect();
+
+        return self;
+    }
+
+    pub(crate) fn with_bigrams(mut self, bigrams: &[(&str, f32)]) -> Self
+    {
+        self.bigrams = Self::table(bigrams);
+
+        return self;
+    }
+
+    /// Skipgrams with `distance` keys in between.
+    pub(crate) fn with_skipgrams(mut self, distance: usize, skipgrams: &[(&str, f32)]) -> Self
+    {
+        let table = Self::table(skipgrams);
+
+        match distance
+        {
+            | 1 => self.skipgrams = table,
+            | 2 => self.skipgrams2 = table,
+            | 3 => self.skipgrams3 = table,
+            | n =>
+            {
+                self.more_skipgrams.insert(format!("skipgrams{n}"), table);
+            },
+        }
+
+        return self;
+    }
+
+    pub(crate) fn with_trigrams(mut self, trigrams: &[(&str, f32)]) -> Self
+    {
+        self.trigrams = Self::table(trigrams);
+
+        return self;
+    }
+
+    fn table(ngrams: &[(&str, f32)]) -> HashMap<String, f32>
+    {
+        return ngrams.iter().map(|(s, p)| (s.to_string(), *p)).collect();
+    }
+}
+
+/// Asserts that two stats are equal up to float rounding.
+#[track_caller]
+pub(crate) fn assert_close(x: f32, y: f32)
+{
+    assert!((x - y).abs() <= 1e-5 * x.abs().max(y.abs()).max(1.), "{x} != {y}");
+}
@@ -1,3 +1,4 @@
 pub mod extension;
 pub mod pair;
+pub mod scored_layout;
 pub mod scorer;
@@ -0,0 +1,441 @@
+use crate::indexed_data::IndexedData;
+use crate::layout::layout::Layout;
+use crate::stats::bigram_stats::{
+    BType,
+    BigramStats,
+};
+use crate::stats::disjoint_stats::{
+    D1Stats,
+    DType,
+};
+use crate::stats::layer_stats::LayerStats;
+use crate::stats::layout_stats::LayoutStats;
+use crate::stats::patterns::{
+    BigramPattern,
+    Patterns,
+    KEYS,
+    PATTERNS,
+};
+use crate::stats::skip_stats::{
+    SkipStats,
+    SkipType,
+};
+use crate::stats::trigram_stats::{
+    TStats,
+    TType,
+};
+use crate::type_def::Fixed;
+use crate::utility::scorer::{
+    Score,
+    Scorer,
+};
+
+/// Skipgrams with up to this many keys in between are in the stats.
+const MAX_SKIP: usize = 3;
+
+/// A layout with its stats and score, which can swap two keys and only go through the n-grams
+/// with those keys to update them, instead of all of them.
+#[derive(Clone)]
+pub struct ScoredLayout
+{
+    pub layout: Layout,
+    pub stats: LayoutStats,
+    pub score: Score,
+    keys: Keys,
+    totals: Totals,
+}
+
+/// Where the character on every position of a layout is in the key set, see
+/// `IndexedData::keys`.
+#[derive(Clone, Copy, PartialEq)]
+struct Keys
+{
+    all: Fixed<Option<usize>>,
+    /// Without punctuation, which only the trigram types count.
+    letters: Fixed<Option<usize>>,
+}
+
+/// The frequencies the stats are made of, before they're turned into percentages. They're kept
+/// in `f64` so that adding and removing n-grams over many swaps doesn't drift.
+#[derive(Clone, Default)]
+struct Totals
+{
+    /// By `BigramPattern`.
+    bigrams: [f64; BigramPattern::ALL.len()],
+    /// By `BigramPattern`, for 1, 2 and 3 keys in between.
+    skipgrams: [[f64; BigramPattern::ALL.len()]; MAX_SKIP],
+    /// By `TType`, without `Invalid`, which is whatever's left.
+    trigrams: [f64; TType::ALL.len()],
+    /// By the `BigramPattern` of the first and last key.
+    disjoints: [f64; BigramPattern::ALL.len()],
+}
+
+impl ScoredLayout
+{
+    pub fn new(data: &IndexedData, scorer: &Scorer, layout: Layout) -> Self
+    {
+        let patterns = &*PATTERNS;
+        let keys = Keys::new(data, &layout.matrix);
+        let mut totals = Totals::default();
+
+        for a in 0 .. KEYS
+        {
+            for b in 0 .. KEYS
+            {
+                totals.bigram(patterns, data, &Keys::NONE, &keys, [a, b]);
+
+                for c in 0 .. KEYS
+                {
+                    totals.trigram(patterns, data, &Keys::NONE, &keys, [a, b, c]);
+                }
+            }
+        }
+
+        let mut scored = Self {
+            stats: LayoutStats {
+                layer_stats: LayerStats::new(&data.language_data, &layout),
+                ..Default::default()
+            },
+            layout,
+            score: Score::default(),
+            keys,
+            totals,
+        };
+
+        scored.rescore(data, scorer);
+
+        return scored;
+    }
+
+    /// Swaps the keys at positions `a` and `b`, and updates the stats and score with the n-grams
+    /// that have either of them. Swapping them again undoes it. Returns the new total score.
+    pub fn swap(&mut self, data: &IndexedData, scorer: &Scorer, a: usize, b: usize) -> f32
+    {
+        if a == b
+        {
+            return self.score.total();
+        }
+
+        let before = self.keys;
+
+        self.layout.matrix.swap(a, b);
+        self.keys.all.swap(a, b);
+        self.keys.letters.swap(a, b);
+
+        // With a character on the layout twice only the first one counts, which a swap can
+        // change for the keys it doesn't touch.
+        if self.keys != Keys::new(data, &self.layout.matrix)
+        {
+            *self = Self::new(data, scorer, std::mem::take(&mut self.layout));
+
+            return self.score.total();
+        }
+
+        self.update(data, &before, a, b);
+        self.rescore(data, scorer);
+
+        return self.score.total();
+    }
+
+    /// Turns the totals into the stats and scores them. The layer stats don't change with swaps
+    /// on the base layer, so they're kept.
+    fn rescore(&mut self, data: &IndexedData, scorer: &Scorer)
+    {
+        let layer_stats = std::mem::take(&mut self.stats.layer_stats);
+        let percent = |p: BigramPattern| (self.totals.bigrams[p as usize] * 100.) as f32;
+
+        let bad_sfb = percent(BigramPattern::BadSFB);
+        let bad_s = percent(BigramPattern::BadS);
+
+        self.stats = self.totals.stats(data, layer_stats);
+        self.score = scorer.weigh(data, &self.layout, &self.stats, bad_sfb, bad_s);
+    }
+
+    /// Goes through every n-gram with a key at `a` or `b` once, and updates the totals by how
+    /// much its frequency changed from `before`.
+    fn update(&mut self, data: &IndexedData, before: &Keys, a: usize, b: usize)
+    {
+        let patterns = &*PATTERNS;
+        let after = &self.keys;
+        let totals = &mut self.totals;
+
+        let outside = || (0 .. KEYS).filter(|x| *x != a && *x != b);
+
+        for x in [a, b]
+        {
+            for y in 0 .. KEYS
+            {
+                totals.bigram(patterns, data, before, after, [x, y]);
+
+                for z in 0 .. KEYS
+                {
+                    totals.trigram(patterns, data, before, after, [x, y, z]);
+                }
+            }
+
+            for y in outside()
+            {
+                totals.bigram(patterns, data, before, after, [y, x]);
+
+                for z in 0 .. KEYS
+                {
+                    totals.trigram(patterns, data, before, after, [y, x, z]);
+                }
+
+                for z in outside()
+                {
+                    totals.trigram(patterns, data, before, after, [y, z, x]);
+                }
+            }
+        }
+    }
+}
+
+impl Keys
+{
+    /// An empty layout, to add up a whole layout from.
+    const NONE: Keys = Keys {
+        all: [None; KEYS],
+        letters: [None; KEYS],
+    };
+
+    fn new(data: &IndexedData, chars: &Fixed<char>) -> Self
+    {
+        let all = data.keys(chars);
+        let mut letters = all;
+
+        for (key, c) in letters.iter_mut().zip(chars)
+        {
+            if c.is_ascii_punctuation()
+            {
+                *key = None;
+            }
+        }
+
+        return Self { all, letters };
+    }
+}
+
+impl Totals
+{
+    /// Adds how much the bigram and skipgrams at `positions` changed from `before` to `after`.
+    #[inline]
+    fn bigram(
+        &mut self,
+        patterns: &Patterns,
+        data: &IndexedData,
+        before: &Keys,
+        after: &Keys,
+        positions: [usize; 2],
+    )
+    {
+        let [a, b] = positions;
+
+        let change = |ngrams: &[f32]| {
+            let p = |keys: &Keys| match (keys.letters[a], keys.letters[b])
+            {
+                | (Some(ka), Some(kb)) => ngrams[ka * KEYS + kb] as f64,
+                | _ => 0.,
+            };
+
+            p(after) - p(before)
+        };
+
+        let bigram = change(&data.bigrams);
+        let skipgrams: [f64; MAX_SKIP] =
+            std::array::from_fn(|n| data.skipgrams_at(n + 1).map_or(0., |s| change(s)));
+
+        if bigram == 0. && skipgrams.iter().all(|p| *p == 0.)
+        {
+            return;
+        }
+
+        for pattern in BigramPattern::ALL
+        {
+            if patterns.is_bigram(a, b, pattern)
+            {
+                self.bigrams[pattern as usize] += bigram;
+
+                for (total, p) in self.skipgrams.iter_mut().zip(skipgrams)
+                {
+                    total[pattern as usize] += p;
+                }
+            }
+        }
+    }
+
+    /// Adds how much the trigram at `positions` changed from `before` to `after`, to its type
+    /// and, when it alternates hands twice, to the disjoints.
+    #[inline]
+    fn trigram(
+        &mut self,
+        patterns: &Patterns,
+        data: &IndexedData,
+        before: &Keys,
+        after: &Keys,
+        positions: [usize; 3],
+    )
+    {
+        let [a, b, c] = positions;
+
+        let change = |before: &Fixed<Option<usize>>, after: &Fixed<Option<usize>>| {
+            let p = |keys: &Fixed<Option<usize>>| match (keys[a], keys[b], keys[c])
+            {
+                | (Some(ka), Some(kb), Some(kc)) => data.trigram(ka, kb, kc) as f64,
+                | _ => 0.,
+            };
+
+            p(after) - p(before)
+        };
+
+        self.trigrams[patterns.trigram(a, b, c) as usize] += change(&before.all, &after.all);
+
+        let left = |i: usize| LayoutStats::is_left_hand(&i);
+
+        if left(a) == left(b) || left(b) == left(c)
+        {
+            return;
+        }
+
+        let p = change(&before.letters, &after.letters);
+
+        if p == 0.
+        {
+            return;
+        }
+
+        for pattern in BigramPattern::ALL
+        {
+            if patterns.is_bigram(a, c, pattern)
+            {
+                self.disjoints[pattern as usize] += p;
+            }
+        }
+    }
+
+    fn stats(&self, data: &IndexedData, layer_stats: LayerStats) -> LayoutStats
+    {
+        let percent = |total: f64| (total * 100.) as f32;
+
+        let valid = self.trigrams.iter().sum::<f64>();
+        let invalid = data.trigram_total() as f64 - valid;
+
+        let skip_stats = |n: usize| {
+            SkipType::ALL
+                .iter()
+                .map(|t| (*t, percent(self.skipgrams[n - 1][t.pattern() as usize])))
+                .collect()
+        };
+
+        return LayoutStats {
+            bigram_stats: BigramStats {
+                inner: BType::ALL
+                    .iter()
+                    .map(|t| (*t, percent(self.bigrams[t.pattern() as usize])))
+                    .collect(),
+            },
+            trigram_stats: TStats {
+                inner: TType::ALL
+                    .iter()
+                    .map(|t| match t
+                    {
+                        | TType::Invalid => (*t, percent(invalid)),
+                        | t => (*t, percent(self.trigrams[*t as usize])),
+                    })
+                    .collect(),
+            },
+            disjoint_stats: D1Stats {
+                inner: DType::ALL
+                    .iter()
+                    .map(|t| (*t, percent(self.disjoints[t.pattern() as usize])))
+                    .collect(),
+            },
+            skip1_stats: SkipStats { inner: skip_stats(1) },
+            skip2_stats: SkipStats { inner: skip_stats(2) },
+            skip3_stats: SkipStats { inner: skip_stats(3) },
+            layer_stats,
+        };
+    }
+}
+
+#[test]
+fn swaps_match_full_recomputation()
+{
+    use crate::config::config::Config;
+    use crate::language_data::LanguageData;
+    use crate::testing::assert_close;
+    use itertools::Itertools;
+    use nanorand::{
+        Rng,
+        WyRand,
+    };
+    use std::collections::HashMap;
+
+    let mut rng = WyRand::new_seed(7);
+
+    // The key set and a character outside of it.
+    let chars = "abcdefghijklmnopqrstuvwxyz',.;?".chars().collect_vec();
+
+    let mut ngrams = |n: usize| -> HashMap<String, f32> {
+        (0 .. n)
+            .map(|_| chars.iter())
+            .multi_cartesian_product()
+            .map(|ngram| (ngram.into_iter().collect(), rng.generate::<f32>() / 1000.))
+            .collect()
+    };
+
+    let language_data = LanguageData {
+        language: "test".to_string(),
+        characters: ngrams(1).into_iter().map(|(c, p)| (c.chars().next().unwrap(), p)).collect(),
+        bigrams: ngrams(2),
+        skipgrams: ngrams(2),
+        skipgrams2: ngrams(2),
+        skipgrams3: ngrams(2),
+        trigrams: ngrams(3),
+        more_skipgrams: HashMap::new(),
+    };
+
+    let data = IndexedData::new(language_data, chars[.. 30].try_into().unwrap());
+    let scorer = Scorer::new(&Config::default());
+
+    // One with a character outside of the key set, one with a character on it twice.
+    for matrix in ["qwertyuiopasdfghjkl;zxcvbnm,.?", "qwertyuiopasdfghjkl;zxcvbnm,.e"]
+    {
+        let layout = Layout {
+            matrix: matrix.chars().collect_vec().try_into().unwrap(),
+            ..Default::default()
+        };
+
+        let mut scored = ScoredLayout::new(&data, &scorer, layout);
+
+        for _ in 0 .. 100
+        {
+            let (a, b) = (rng.generate_range(0 .. KEYS), rng.generate_range(0 .. KEYS));
+
+            let total = scored.swap(&data, &scorer, a, b);
+            let stats = LayoutStats::new(&data, &scored.layout);
+
+            assert_close(total, scorer.total_score(&data, &scored.layout));
+
+            for (t, p) in stats.bigram_stats.inner.iter()
+            {
+                assert_close(scored.stats[*t], *p);
+            }
+
+            for (t, p) in stats.trigram_stats.inner.iter()
+            {
+                assert_close(scored.stats[*t], *p);
+            }
+
+            for (t, p) in stats.disjoint_stats.inner.iter()
+            {
+                assert_close(scored.stats[*t], *p);
+            }
+
+            for (t, p) in stats.skip3_stats.inner.iter()
+            {
+                assert_close(scored.stats.skip3_stats[*t], *p);
+            }
+        }
+    }
+}
@@ -1,20 +1,351 @@
+use crate::config::config::Config;
+use crate::hand::finger::Finger;
+use crate::hand::finger::Finger::*;
+use crate::hand::hand::Hand::Left;
+use crate::indexed_data::IndexedData;
+use crate::layout::layout::Layout;
+use crate::stats::bigram_stats::BType::*;
+use crate::stats::breakdown::Metric;
 use crate::stats::bigram_stats::BigramStats;
+use crate::stats::heatmap_stats::HeatmapStats;
+use crate::stats::layout_stats::LayoutStats;
+use crate::stats::patterns::BigramPattern;
+use crate::stats::skip_stats::SkipType;
+use crate::stats::trigram_stats::TType::*;
+use crate::stats::usage_stats::UsageStats;
+use indexmap::IndexMap;
+use std::fmt;
+use std::fmt::{
+    Display,
+    Formatter,
+};
 
-pub struct Scorer {}
+/// A stat of a layout and how much it counts towards the total score.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct ScoreTerm
+{
+    pub value: f32,
+    pub weight: f32,
+}
+
+impl ScoreTerm
+{
+    pub fn score(&self) -> f32
+    {
+        return self.value * self.weight;
+    }
+}
+
+/// The score of a layout, per stat. Higher is better.
+#[derive(Clone, Debug, Default)]
+pub struct Score
+{
+    pub terms: IndexMap<String, ScoreTerm>,
+}
+
+impl Score
+{
+    pub fn total(&self) -> f32
+    {
+        return self.terms.values().map(ScoreTerm::score).sum();
+    }
+
+    fn add(&mut self, name: &str, value: f32, weight: f32)
+    {
+        self.terms.insert(name.to_string(), ScoreTerm { value, weight });
+    }
+}
+
+/// Combines the stats of a layout into a single score, using the weights of the config.
+pub struct Scorer
+{
+    config: Config,
+}
 
 impl Scorer
 {
-    pub fn total_score(&self, bigram_stats: &BigramStats) -> f32
+    pub fn new(config: &Config) -> Self
     {
-        // let scissors = self.scissor_score(layout);
-        // let lsbs = self.lateral_stretch_bigram_score(layout);
-        // let pinky_ring = self.pinky_ring_score(layout);
+        return Self {
+            config: config.clone(),
+        };
+    }
 
-        todo!()
+    pub fn total_score(&self, data: &IndexedData, layout: &Layout) -> f32
+    {
+        let stats = LayoutStats::new(data, layout);
+
+        return self.score(data, layout, &stats).total();
+    }
+
+    /// Weighs every stat of a layout. Same finger stats are split into the ones on pinkies and
+    /// rings, which use the `bad` weights, and the rest.
+    pub fn score(&self, data: &IndexedData, layout: &Layout, stats: &LayoutStats) -> Score
+    {
+        let chars = &layout.matrix;
+        let keys = data.keys(chars);
+
+        let bad_sfb = BigramStats::p1(chars, &keys, &data.bigrams, BigramPattern::BadSFB);
+        let bad_s = BigramStats::p1(chars, &keys, &data.bigrams, BigramPattern::BadS);
+
+        return self.weigh(data, layout, stats, bad_sfb, bad_s);
     }
 
-    pub fn same_finger_bigrams(&self) -> Vec<([u8; 2], f32)>
+    /// `score`, with the same finger bigrams and the scissors on pinkies and rings already added
+    /// up.
+    pub(crate) fn weigh(
+        &self,
+        data: &IndexedData,
+        layout: &Layout,
+        stats: &LayoutStats,
+        bad_sfb: f32,
+        bad_s: f32,
+    ) -> Score
     {
-        return Vec::new();
+        let weight = |metric: Metric| self.weight(metric, false);
+        let bad_weight = |metric: Metric| self.weight(metric, true);
+
+        let mut score = Score::default();
+
+        score.add("SFB", stats[SFB] - bad_sfb, weight(Metric::Bigram(SFB)));
+        score.add("Bad SFB", bad_sfb, bad_weight(Metric::Bigram(SFB)));
+        score.add("LSB", stats[LSB], weight(Metric::Bigram(LSB)));
+        score.add("S", stats[S] - bad_s, weight(Metric::Bigram(S)));
+        score.add("Bad S", bad_s, bad_weight(Metric::Bigram(S)));
+        score.add("IRB", stats[IRB], weight(Metric::Bigram(IRB)));
+        score.add("ORB", stats[ORB], weight(Metric::Bigram(ORB)));
+        score.add("AB", stats[AB], weight(Metric::Bigram(AB)));
+        score.add("Repeat", stats[Repeat], weight(Metric::Bigram(Repeat)));
+
+        let redirects = stats[Redirect] + stats[RedirectSFS];
+        let bad_redirects = stats[BadRedirect] + stats[BadRedirectSFS];
+
+        // Same finger bigrams and skipgrams in trigrams are already counted by the bigram and
+        // skipgram stats.
+        score.add("SFT", stats[SFT], weight(Metric::Trigram(SFT)));
+        score.add("SFBT", stats[SFBT], weight(Metric::Trigram(SFBT)));
+        score.add("IRT", stats[IRT], weight(Metric::Trigram(IRT)));
+        score.add("ORT", stats[ORT], weight(Metric::Trigram(ORT)));
+        score.add("OneHandIn", stats[OneHandIn], weight(Metric::Trigram(OneHandIn)));
+        score.add("OneHandOut", stats[OneHandOut], weight(Metric::Trigram(OneHandOut)));
+        score.add("Redirect", redirects, weight(Metric::Trigram(Redirect)));
+        score.add("BadRedirect", bad_redirects, weight(Metric::Trigram(BadRedirect)));
+        score.add("AT", stats[AT] + stats[AltSFS], weight(Metric::Trigram(AT)));
+
+        for (t, value) in stats.disjoint_stats.inner.iter()
+        {
+            score.add(format!("{t:?}").as_str(), *value, weight(Metric::Disjoint(*t)));
+        }
+
+        let skips = [
+            (1, &stats.skip1_stats.inner),
+            (2, &stats.skip2_stats.inner),
+            (3, &stats.skip3_stats.inner),
+        ];
+
+        for (n, skip) in skips
+        {
+            for t in [SkipType::SFB, SkipType::LSB, SkipType::S]
+            {
+                let value = *skip.get(&t).unwrap_or(&0.);
+
+                score.add(format!("S{n}{t:?}").as_str(), value, weight(Metric::Skip(n, t)));
+            }
+        }
+
+        let heatmap = HeatmapStats::new(data, layout, self.config.info.keyboard_type);
+
+        score.add("Heatmap", heatmap.total(), self.config.effort.heatmap);
+
+        let activation = stats.layer_stats.total_activation();
+
+        score.add("Layer keys", activation, self.config.effort.layer_activation);
+
+        let (imbalance, overuse) = self.effort(data, layout);
+
+        score.add("Hand balance", imbalance, self.config.effort.hand.weight);
+        score.add("Finger use", overuse, self.config.effort.fingers.overuse);
+
+        return score;
     }
+
+    /// The weight of one percent of a metric in the score, or 0 for metrics that aren't scored.
+    /// `bad` picks the weight of same finger bigrams and scissors on pinkies and rings.
+    pub fn weight(&self, metric: Metric, bad: bool) -> f32
+    {
+        let bigrams = &self.config.bigrams;
+        let same_finger = &bigrams.same_finger;
+        let scissors = &bigrams.scissors;
+        let trigrams = &self.config.trigrams;
+        let lateral = self.config.effort.fingers.lateral_move;
+
+        return match metric
+        {
+            | Metric::Bigram(SFB) if bad => same_finger.bad,
+            | Metric::Bigram(SFB) => same_finger.base,
+            | Metric::Bigram(S) if bad => scissors.bad,
+            | Metric::Bigram(S) => scissors.base,
+            | Metric::Bigram(LSB) => lateral,
+            | Metric::Bigram(IRB) => bigrams.rolls.in_roll,
+            | Metric::Bigram(ORB) => bigrams.rolls.out_roll,
+            | Metric::Bigram(AB) => bigrams.alternates.base,
+            | Metric::Bigram(Repeat) => bigrams.repeats.base,
+            | Metric::Trigram(SFT) => trigrams.same_finger.sft,
+            | Metric::Trigram(SFBT) => trigrams.same_finger.sfbt,
+            | Metric::Trigram(IRT) => trigrams.rolls.in_roll,
+            | Metric::Trigram(ORT) => trigrams.rolls.out_roll,
+            | Metric::Trigram(OneHandIn | OneHandOut) => trigrams.one_hands.base,
+            | Metric::Trigram(Redirect | RedirectSFS) => trigrams.redirects.base,
+            | Metric::Trigram(BadRedirect | BadRedirectSFS) => trigrams.redirects.bad,
+            | Metric::Trigram(AT | AltSFS) => trigrams.alternates.base,
+            | Metric::Trigram(Other | Invalid) => 0.,
+            | Metric::Disjoint(t) => self.config.disjoints.of(t),
+            | Metric::Skip(n @ 1 ..= 3, t) =>
+            {
+                let decay = ((n + 1) as f32).powf(-same_finger.decay);
+
+                match t
+                {
+                    | SkipType::SFB => same_finger.base * decay,
+                    | SkipType::LSB => lateral * decay,
+                    | SkipType::S => scissors.base * decay,
+                    | _ => 0.,
+                }
+            },
+            | Metric::Skip(..) => 0.,
+        };
+    }
+
+    /// How far the hand usage is from the ratio of `[effort.hand]`, and how much each finger is
+    /// used more than its share by `[effort.fingers.preference]`, both in percentage points.
+    fn effort(&self, data: &IndexedData, layout: &Layout) -> (f32, f32)
+    {
+        let hand = &self.config.effort.hand;
+        let preference = &self.config.effort.fingers.preference;
+        let usage = UsageStats::new(data, layout, &self.config.finger_to_column);
+
+        let strength = |finger: Finger| preference.of(finger);
+
+        let total = usage.fingers.values().sum::<f32>();
+        let hand_strength = [LP, LR, LM, LI].map(strength).iter().sum::<f32>();
+        let left_share = hand.left / (hand.left + hand.right);
+
+        let left = usage.hands[&Left];
+        let mut overuse = 0.;
+
+        for (finger, used) in usage.fingers
+        {
+            let hand_share = match finger.hand()
+            {
+                | Left => left_share,
+                | _ => 1. - left_share,
+            };
+
+            let share = total * hand_share * strength(finger) / hand_strength;
+
+            if strength(finger) > 0.
+            {
+                overuse += (used - share).max(0.);
+            }
+        }
+
+        return ((left - total * left_share).abs(), overuse);
+    }
+}
+
+impl Display for Score
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result
+    {
+        let mut format = "Score:\n".to_string();
+
+        self.terms
+            .iter()
+            .filter(|(_, term)| term.weight != 0.)
+            .for_each(|(key, term)| {
+                let s = format!(
+                    "  {:12} {:>7.3}% x {:>6.2} = {:>8.3}\n",
+                    key,
+                    term.value,
+                    term.weight,
+                    term.score()
+                );
+
+                format.push_str(s.as_str());
+            });
+
+        format.push_str(format!("  {:12} {:>36.3}", "Total", self.total()).as_str());
+
+        write!(f, "{}", format)
+    }
+}
+
+#[test]
+fn total()
+{
+    let mut score = Score::default();
+
+    score.add("SFB", 2., -1.5);
+    score.add("IRT", 10., 3.);
+    score.add("AB", 40., 0.);
+
+    assert_eq!(score.total(), 2. * -1.5 + 10. * 3.);
+}
+
+#[test]
+fn weigh()
+{
+    use crate::language_data::LanguageData;
+    use crate::testing::assert_close;
+    use itertools::Itertools;
+
+    // Only the home row is used, by each finger as much as its strength in the default config,
+    // so the hands and fingers are used exactly as much as they should be.
+    let language_data = LanguageData::test()
+        .with_characters(&[
+            ('k', 0.01),
+            ('l', 0.14),
+            ('m', 0.2),
+            ('n', 0.2),
+            ('q', 0.2),
+            ('r', 0.2),
+            ('s', 0.14),
+            ('t', 0.01),
+        ])
+        .with_bigrams(&[("ak", 0.3), ("cm", 0.5), ("ab", 0.2)])
+        .with_skipgrams(1, &[("ak", 1.0)]);
+
+    let chars = "abcdefghijklmnopqrstuvwxyz',.;".chars().collect_vec();
+    let data = IndexedData::new(language_data, chars.clone().try_into().unwrap());
+
+    let layout = Layout {
+        matrix: chars.try_into().unwrap(),
+        ..Default::default()
+    };
+
+    let mut config = Config::default();
+    config.bigrams.same_finger.decay = 2.;
+
+    let scorer = Scorer::new(&config);
+    let stats = LayoutStats::new(&data, &layout);
+    let score = scorer.score(&data, &layout, &stats);
+
+    // `ak` is on a pinky, `cm` on a middle finger.
+    assert_close(score.terms["SFB"].value, 50.);
+    assert_close(score.terms["Bad SFB"].value, 30.);
+    assert_close(score.terms["SFB"].value + score.terms["Bad SFB"].value, stats[SFB]);
+
+    for n in 1 .. 4
+    {
+        let weight = config.bigrams.same_finger.base * ((n + 1) as f32).powf(-2.);
+
+        assert_close(score.terms[format!("S{n}SFB").as_str()].weight, weight);
+    }
+
+    assert_close(score.terms["S1SFB"].value, 100.);
+
+    let (imbalance, overuse) = scorer.effort(&data, &layout);
+
+    assert_close(imbalance, 0.);
+    assert_close(overuse, 0.);
 }
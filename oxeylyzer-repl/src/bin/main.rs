@@ -1,5 +1,19 @@
 use oxeylyzer_repl::repl::Repl;
 
 fn main() {
-    return Repl::new().run();
+    let args = std::env::args().collect::<Vec<_>>();
+
+    let config_path = args
+        .iter()
+        .position(|a| a == "--config" || a == "-c")
+        .and_then(|i| args.get(i + 1));
+
+    match Repl::new(config_path.map(String::as_str)) {
+        | Ok(mut repl) => repl.run(),
+        | Err(e) => {
+            eprintln!("{e}");
+
+            std::process::exit(1);
+        },
+    }
 }
@@ -8,19 +8,51 @@ xflags! {
             required name_or_number: String
         }
 
-        /// Compare two layouts.
+        /// Compare two layouts. Numbers select layouts of this session.
         cmd compare c comp cmp {
             required name1: String
             required name2: String
         }
 
-        /// Rank all layouts for the loaded language.
+        /// Rank all layouts for the loaded language, and the ones of this session, best first, or
+        /// worst first with -a true.
         /// -c: 1, 2, 3, 4
         cmd rank {
            optional -a, --asc order: bool
            optional -c, --columns columns: String
         }
 
+        /// Generate layouts from the keys of the loaded language in languages_default.cfg, and
+        /// show the best ones.
+        /// -o: annealing (default) or genetic
+        cmd generate g gen {
+            required amount: usize
+            optional -o, --optimizer optimizer: String
+        }
+
+        /// Optimize a layout, keeping its pinned keys in place, and show the best distinct
+        /// results. They are numbered, so other commands can select them by number.
+        /// -o: annealing (default) or genetic
+        cmd improve i optimize {
+            required name: String
+            required amount: usize
+            optional -o, --optimizer optimizer: String
+        }
+
+        /// Search for the layouts that no other layout beats on all of the given terms of the
+        /// score, and list them with their value for each. They are added to the session.
+        /// Metrics: the names in the score of analyze, separated by commas, e.g. SFB,IRT,Heatmap
+        /// -l: start from this layout, keeping its pinned keys in place
+        /// -g: how many generations to search for, 100 by default
+        cmd pareto front {
+            required metrics: String
+            optional -l, --layout name: String
+            optional -g, --generations generations: usize
+        }
+
+        /// List the layouts generated, improved or found by pareto in this session, with their number.
+        cmd session s {}
+
         /// Shows the top n same finger bigrams in a layout.
         cmd sfbs {
             required name: String
@@ -33,12 +65,65 @@ xflags! {
             optional -c, --count count: usize
         }
 
+        /// Shows the n-grams that make up a stat of a layout, biggest impact on the score first.
+        /// Metrics: the names in analyze, e.g. SFB, Redirect, D1LSB, S2SFB
+        cmd top {
+            required metric: String
+            required name: String
+            optional -c, --count count: usize
+        }
+
         /// Gives information about a certain n-gram.
         /// For bigrams, skipgram info will be provided.
         cmd ngram n occ freq {
             required ngram: String
         }
 
+        /// Show which keys of a layout are pinned, so improving it won't move them.
+        /// -t: the keys to pin or unpin, e.g. "eat"
+        cmd pins {
+            required name: String
+            optional -t, --toggle keys: String
+        }
+
+        /// Check a layout for duplicate or missing keys. Without a name, every layout with
+        /// duplicate or missing keys is listed.
+        cmd validate {
+            optional name: String
+        }
+
+        /// List layouts that are the same up to mirroring and swapping index columns. These have
+        /// the same SFBs, but can have a different heatmap and score.
+        /// -n: also list layouts that differ by at most this many keys
+        cmd dupes {
+            optional -n, --keys keys: usize
+        }
+
+        /// Save a layout as a .kb file in the layouts folder of the loaded language.
+        cmd save {
+            required name: String
+            required new_name: String
+        }
+
+        /// Export a layout to a keyboard configuration format, printing it or writing it to a file.
+        /// Formats: xkb, qmk, zmk
+        cmd export {
+            required format: String
+            required name: String
+            optional -o, --output path: String
+        }
+
+        /// Import a layout from another format and save it as a .kb file under a new name.
+        /// Formats: kle, klc, cmini
+        cmd import {
+            required format: String
+            required path: String
+            required name: String
+        }
+
+        /// Show the config in use and where it was loaded from.
+        cmd config {}
+
         /// Quit.
         cmd quit q exit {}
     }
@@ -1,47 +1,95 @@
 use crate::flags::{
     Analyze,
     Compare,
+    Dupes,
+    Export,
+    Generate,
+    Import,
+    Improve,
     Ngram,
+    Pareto,
+    Pins,
     Rank,
+    Save,
     Sfbs,
     Sfts,
+    Top,
+    Validate,
 };
 use itertools::Itertools;
-use oxeylyzer_core::config::config::Config;
+use oxeylyzer_core::config::config::{
+    Config,
+    ConfigSource,
+};
+use oxeylyzer_core::config::error::ConfigError;
+use oxeylyzer_core::config::pins;
 use oxeylyzer_core::data_dir::DataFetch;
-use oxeylyzer_core::language_data::LanguageData;
+use oxeylyzer_core::export::qmk::Qmk;
+use oxeylyzer_core::export::xkb::Xkb;
+use oxeylyzer_core::export::zmk::Zmk;
+use oxeylyzer_core::generation;
+use oxeylyzer_core::generation::annealing::Annealing;
+use oxeylyzer_core::generation::pareto;
+use oxeylyzer_core::generation::{
+    Generator,
+    Optimizer,
+};
+use oxeylyzer_core::indexed_data::IndexedData;
+use oxeylyzer_core::layout::keyboard_type::KeyboardType;
 use oxeylyzer_core::layout::layout::Layout;
-use oxeylyzer_core::stats::bigram_stats::BType::*;
-use oxeylyzer_core::stats::disjoint_stats::DType::*;
+use oxeylyzer_core::layout::validation::LayoutValidation;
+use oxeylyzer_core::stats::breakdown::{
+    Breakdown,
+    Metric,
+};
+use oxeylyzer_core::stats::fspeed_stats::FspeedStats;
+use oxeylyzer_core::stats::heatmap_stats::HeatmapStats;
 use oxeylyzer_core::stats::layout_stats::LayoutStats;
-use oxeylyzer_core::stats::trigram_stats::TType::*;
+use oxeylyzer_core::stats::usage_stats::UsageStats;
 use oxeylyzer_core::type_def::Fixed;
+use oxeylyzer_core::utility::scored_layout::ScoredLayout;
+use oxeylyzer_core::utility::scorer::Scorer;
 use std::collections::HashMap;
 
 pub struct Repl
 {
     config: Config,
-    language_data: LanguageData,
+    config_source: ConfigSource,
+    data: IndexedData,
 
     layouts: HashMap<String, Layout>,
+    /// Pins changed in this session, per layout. Other layouts use the pins of the config.
+    pins: HashMap<String, pins::Pins>,
+    /// Layouts made in this session and where they came from, selected by their number in place
+    /// of a name.
+    session: Vec<(String, Layout)>,
 }
 
 impl Repl
 {
-    pub fn new() -> Self
+    /// Starts the repl with the config at `config_path`, or the first one found otherwise.
+    pub fn new(config_path: Option<&str>) -> Result<Self, ConfigError>
     {
-        let config = Config::default();
+        let (config, config_source) = Config::load(config_path)?;
 
-        let language_data = Self::load_language(&config.info.language);
+        let data = DataFetch::indexed_data(&config.info.language);
 
         let fetch = DataFetch::layout_files_in_language(config.info.language.as_str());
-        let layouts = DataFetch::load_layouts(fetch);
+        let (layouts, errors) = DataFetch::load_layouts(fetch);
+
+        for e in errors
+        {
+            eprintln!("Skipped {e}");
+        }
 
-        return Self {
+        return Ok(Self {
             layouts,
+            pins: HashMap::new(),
+            session: Vec::new(),
             config,
-            language_data,
-        };
+            config_source,
+            data,
+        });
     }
 
     pub fn readline() -> std::io::Result<String>
@@ -102,9 +150,21 @@ impl Repl
             | Analyze(o) => self.analyze(o),
             | Compare(o) => self.compare(o),
             | Rank(o) => self.rank(o),
+            | Generate(o) => self.generate(o),
+            | Improve(o) => self.improve(o),
+            | Pareto(o) => self.pareto(o),
+            | Session(_) => self.session(),
             | Sfbs(o) => self.sfbs(o),
             | Sfts(o) => self.sfts(o),
+            | Top(o) => self.top(o),
             | Ngram(o) => self.ngram(o),
+            | Pins(o) => self.pins(o),
+            | Validate(o) => self.validate(o),
+            | Dupes(o) => self.dupes(o),
+            | Save(o) => self.save(o),
+            | Export(o) => self.export(o),
+            | Import(o) => self.import(o),
+            | Config(_) => self.config(),
             | Quit(_) =>
             {
                 return Ok(true);
@@ -121,39 +181,42 @@ impl Repl
         let name0 = o.name1;
         let name1 = o.name2;
 
-        let mut result = format!("\n{name0:31}{name1}\n");
-
-        let binding = self.analyze(Analyze {
-            name_or_number: name0,
-        });
-
-        let s0 = binding.split('\n').collect_vec();
-
-        let binding = self.analyze(Analyze {
-            name_or_number: name1,
-        });
+        let (layout0, layout1) = match (self.layout_by_name(&name0), self.layout_by_name(&name1))
+        {
+            | (Some(l0), Some(l1)) => (l0, l1),
+            | (None, _) => return format!("'{name0}' does not exist!"),
+            | (_, None) => return format!("'{name1}' does not exist!"),
+        };
 
-        let s1 = binding.split('\n').collect_vec();
+        let (grid0, usage0, stats0) = self.analysis(&layout0);
+        let (grid1, usage1, stats1) = self.analysis(&layout1);
 
-        let compare_map = s0
-            .into_iter()
-            .zip(s1)
-            .map(|(a, b)| {
-                return if a.chars().count() > 64
-                {
-                    format!("{a}{:10}{b}", " ")
-                }
-                else
-                {
-                    format!("{a:31}{b}")
-                };
-            })
-            .collect_vec()
-            .join("\n");
+        let side_by_side = |s0: &str, s1: &str| {
+            return s0
+                .split('\n')
+                .zip_longest(s1.split('\n'))
+                .map(|pair| {
+                    let (a, b) = pair.or_default();
 
-        result.push_str(compare_map.as_str());
+                    return if a.chars().count() > 64
+                    {
+                        format!("{a}{:10}{b}", " ")
+                    }
+                    else
+                    {
+                        format!("{a:31}{b}")
+                    };
+                })
+                .join("\n");
+        };
 
-        return result;
+        // The usage gets a table of its own, as it has a column per layout already.
+        return format!(
+            "\n{name0:31}{name1}\n{}\n\n{}\n{}",
+            side_by_side(&grid0, &grid1),
+            usage0.compare(&usage1),
+            side_by_side(&stats0, &stats1),
+        );
     }
 
     fn analyze(&mut self, o: Analyze) -> String
@@ -166,54 +229,96 @@ impl Repl
             return format!("'{name}' does not exist!");
         }
 
-        let layout = layout.unwrap();
+        let (grid, usage, stats) = self.analysis(&layout.unwrap());
 
-        let stats = LayoutStats::new(&self.language_data, &layout);
+        return format!("{grid}\n\n{usage}\n{stats}");
+    }
 
-        let layout_str = Self::heatmap(&self.language_data.characters, &layout.matrix).join("\n");
+    /// The heatmap coloured grid of a layout, its usage and the rest of its stats.
+    fn analysis(&self, layout: &Layout) -> (String, UsageStats, String)
+    {
+        let stats = LayoutStats::new(&self.data, layout);
 
-        return format!(
-            "{layout_str}\n\n\
+        let keyboard_type = self.config.info.keyboard_type;
+        let heatmap = HeatmapStats::new(&self.data, layout, keyboard_type);
+
+        let layout_str = Self::heatmap(&heatmap.positions, &layout.matrix).join("\n");
+
+        let usage = UsageStats::new(&self.data, layout, &self.config.finger_to_column);
+        let fspeed = FspeedStats::new(&self.data, layout, &self.config);
+        let score = Scorer::new(&self.config).score(&self.data, layout, &stats);
+
+        let mut result = format!(
+            "{}\n\
+            {}\n\
+            {}\n\
             {}\n\
             {}\n\
             {}",
-            stats.bigram_stats, stats.trigram_stats, stats.disjoint_stats,
+            fspeed,
+            heatmap,
+            stats.bigram_stats,
+            stats.trigram_stats,
+            stats.disjoint_stats,
+            score,
         );
+
+        if !layout.layers.is_empty()
+        {
+            result.push_str(format!("\n{}", stats.layer_stats).as_str());
+        }
+
+        let outside = self.data.outside_key_set(&layout.matrix);
+
+        if !outside.is_empty()
+        {
+            result.push_str(
+                format!(
+                    "\nNot in the key set of {}, so not counted in any stat: {}",
+                    self.data.language,
+                    outside.iter().join(" ")
+                )
+                .as_str(),
+            );
+        }
+
+        return (layout_str, usage, result);
     }
 
     pub fn rank(&self, rank: Rank) -> String
     {
         use rayon::iter::*;
 
+        let scorer = Scorer::new(&self.config);
+
+        let session = self
+            .session
+            .iter()
+            .enumerate()
+            .map(|(number, (_, layout))| (number.to_string(), layout));
+
         let mut v = self
             .layouts
             .iter()
+            .map(|(name, layout)| (name.clone(), layout))
+            .chain(session)
             .par_bridge()
             .map(|(name, layout)| {
-                let stats = LayoutStats::new(&self.language_data, &layout);
+                let metric = scorer.total_score(&self.data, layout);
 
-                let a = [
-                    stats[SFB],
-                    stats[D1SFB],
-                    stats[SFT],
-                    stats[LSB],
-                    stats[D1LSB],
-                ];
-
-                let metric = a.into_iter().fold(0., |c, x| c + x);
-
-                return (name.clone(), metric);
+                return (name, metric);
             })
             .collect::<Vec<_>>();
 
+        // Best first, unless asked for ascending order.
         v.sort_by(|(_, s0), (_, s1)| {
-            if rank.asc.unwrap_or(true)
+            if rank.asc.unwrap_or(false)
             {
-                s1.partial_cmp(s0).unwrap()
+                s0.total_cmp(s1)
             }
             else
             {
-                s0.partial_cmp(s1).unwrap()
+                s1.total_cmp(s0)
             }
         });
 
@@ -223,8 +328,152 @@ impl Repl
             .join("\n");
     }
 
+    fn generate(&mut self, o: Generate) -> String
+    {
+        let optimizer = match Self::optimizer(o.optimizer)
+        {
+            | Ok(optimizer) => optimizer,
+            | Err(e) => return e,
+        };
+
+        let base = Generator::new(&self.data, &self.config).key_set_layout();
+        let layouts = self.run_generator(&base, &pins::Pins::default(), o.amount, &optimizer);
+
+        return self.show_best(&base, "generated", layouts, 10);
+    }
+
+    fn improve(&mut self, o: Improve) -> String
+    {
+        let base = match self.layout_by_name(o.name.as_str())
+        {
+            | Some(layout) => layout,
+            | None => return format!("'{}' does not exist!", o.name),
+        };
+
+        let optimizer = match Self::optimizer(o.optimizer)
+        {
+            | Ok(optimizer) => optimizer,
+            | Err(e) => return e,
+        };
+
+        let pins = self.pins_of(o.name.as_str());
+        let layouts = self.run_generator(&base, &pins, o.amount, &optimizer);
+        let origin = format!("improved {}", o.name);
+
+        return self.show_best(&base, origin.as_str(), layouts, 10);
+    }
+
+    fn pareto(&mut self, o: Pareto) -> String
+    {
+        let (base, pins) = match o.layout
+        {
+            | Some(name) => match self.layout_by_name(name.as_str())
+            {
+                | Some(layout) => (layout, self.pins_of(name.as_str())),
+                | None => return format!("'{name}' does not exist!"),
+            },
+            | None =>
+            {
+                let base = Generator::new(&self.data, &self.config).key_set_layout();
+
+                (base, pins::Pins::default())
+            },
+        };
+
+        let scorer = Scorer::new(&self.config);
+        let score = scorer.score(&self.data, &base, &LayoutStats::new(&self.data, &base));
+        let metrics = o.metrics.split(',').map(|m| m.trim().to_string()).collect_vec();
+
+        let objectives = match pareto::Pareto::objectives(&score, &metrics)
+        {
+            | Ok(objectives) => objectives,
+            | Err(e) => return e,
+        };
+
+        let search = pareto::Pareto {
+            generations: o.generations.unwrap_or(pareto::Pareto::default().generations),
+            ..Default::default()
+        };
+
+        let progress = Self::progress_bar(search.generations, "generations");
+        let front = search.run(&self.data, &scorer, &base, &pins, &objectives, || progress.inc(1));
+
+        progress.finish_and_clear();
+
+        let origin = format!("pareto {}", objectives.join(","));
+        let width = |name: &String| name.len().max(8);
+
+        let header = objectives
+            .iter()
+            .map(|name| format!("{name:>w$}", w = width(name)))
+            .join("  ");
+
+        let mut result = format!("{:6}{header}  {:>8}\n", "", "score");
+
+        for l in generation::distinct(&base, front)
+        {
+            let values = objectives
+                .iter()
+                .map(|name| format!("{:>w$.3}", l.score.terms[name].value, w = width(name)))
+                .join("  ");
+
+            let number = format!("{}:", self.session.len());
+
+            result.push_str(format!("{number:6}{values}  {:>8.3}\n", l.score.total()).as_str());
+
+            self.session.push((origin.clone(), l.layout));
+        }
+
+        return result.trim_end().to_string();
+    }
+
+    fn session(&self) -> String
+    {
+        if self.session.is_empty()
+        {
+            return "No layouts were made in this session yet.".to_string();
+        }
+
+        let scorer = Scorer::new(&self.config);
+
+        return self
+            .session
+            .iter()
+            .enumerate()
+            .map(|(number, (origin, layout))| {
+                let score = scorer.total_score(&self.data, layout);
+
+                self.show_numbered(format!("{number}: {origin}").as_str(), layout, score)
+            })
+            .join("\n\n");
+    }
+
     fn sfbs(&self, o: Sfbs) -> String
     {
+        return self.top(Top {
+            metric: "SFB".to_string(),
+            name: o.name,
+            count: o.count,
+        });
+    }
+
+    fn sfts(&self, o: Sfts) -> String
+    {
+        return self.top(Top {
+            metric: "SFT".to_string(),
+            name: o.name,
+            count: o.count,
+        });
+    }
+
+    fn top(&self, o: Top) -> String
+    {
+        let metric = match o.metric.parse::<Metric>()
+        {
+            | Ok(metric) => metric,
+            | Err(e) => return e,
+        };
+
         return match self.layout_by_name(o.name.as_str())
         {
             | None =>
@@ -233,116 +482,338 @@ impl Repl
             },
             | Some(layout) =>
             {
-                let top_n = o.count.unwrap_or(10).min(48);
+                let scorer = Scorer::new(&self.config);
+                let breakdown = Breakdown::new(&self.data, &layout.matrix, metric, &scorer);
+                let top_n = o.count.unwrap_or(10).min(breakdown.ngrams.len());
+
+                let mut response = format!(
+                    "Top {top_n} of {} {metric} n-grams for {} by impact on the score \
+                    ({:.3}% total, {:.3} score):\n",
+                    breakdown.ngrams.len(),
+                    o.name,
+                    breakdown.total(),
+                    breakdown.impact()
+                );
+
+                breakdown.ngrams.iter().take(top_n).for_each(|(s, f, i)| {
+                    response.push_str(format!("{} {:.5}% {:.5}\n", s, f, i).as_str())
+                });
+
+                return response;
+            },
+        };
+    }
+
+    fn pins(&mut self, o: Pins) -> String
+    {
+        let layout = match self.layout_by_name(o.name.as_str())
+        {
+            | None =>
+            {
+                return format!("Layout \"{}\" does not exist.", o.name);
+            },
+            | Some(layout) => layout,
+        };
+
+        let mut pins = self.pins_of(o.name.as_str());
 
-                let mut response = format!("Top {top_n} SFBs for {}:\n", o.name);
+        if let Some(keys) = o.toggle
+        {
+            for c in keys.chars()
+            {
+                match layout.matrix.iter().position(|k| *k == c)
+                {
+                    | None => return format!("'{c}' is not on \"{}\".", o.name),
+                    | Some(i) => pins.toggle(i),
+                }
+            }
+
+            self.pins.insert(o.name.clone(), pins.clone());
+        }
+
+        let rows = layout
+            .matrix
+            .chunks(10)
+            .zip(pins.to_string().lines())
+            .map(|(keys, marks)| {
+                let left = keys[.. 5].iter().join(" ");
+                let right = keys[5 ..].iter().join(" ");
+
+                format!("{left}  {right}    {marks}")
+            })
+            .join("\n");
+
+        return format!("Pins for {}:\n{rows}", o.name);
+    }
+
+    fn validate(&self, o: Validate) -> String
+    {
+        let key_set = self.data.key_set;
+        let language_data = &self.data.language_data;
+
+        return match o.name
+        {
+            | Some(name) => match self.layout_by_name(name.as_str())
+            {
+                | None => format!("Layout \"{name}\" does not exist."),
+                | Some(layout) =>
+                {
+                    let validation = LayoutValidation::new(&layout, &key_set, language_data);
 
-                let mut v = Vec::new();
+                    format!("{name}:\n{validation}")
+                },
+            },
+            | None =>
+            {
+                let mut response = String::new();
 
-                for i in 0 .. 30
+                for name in self.layouts.keys().sorted()
                 {
-                    for j in 0 .. 30
+                    let validation =
+                        LayoutValidation::new(&self.layouts[name], &key_set, language_data);
+
+                    if !validation.is_valid()
                     {
-                        if LayoutStats::is_sf(&mut [i as u8, j as u8])
-                        {
-                            let c0 = layout.matrix[i];
-                            let c1 = layout.matrix[j];
+                        response.push_str(format!("{name}:\n{validation}\n").as_str());
+                    }
+                }
+
+                if response.is_empty()
+                {
+                    response.push_str("Every layout has all keys of the language exactly once.");
+                }
+
+                response
+            },
+        };
+    }
+
+    fn dupes(&self, o: Dupes) -> String
+    {
+        let max_keys = o.keys.unwrap_or(0);
+
+        let names = self.layouts.keys().sorted().collect_vec();
+
+        let groups = names
+            .iter()
+            .map(|name| (self.layouts[*name].fingerprint(), *name))
+            .into_group_map();
+
+        let mut response = String::from("Equivalent layouts:\n");
+
+        groups
+            .iter()
+            .filter(|(_, group)| group.len() > 1)
+            .sorted_by_key(|(_, group)| group[0])
+            .for_each(|(fingerprint, group)| {
+                let names = group.iter().join(", ");
+
+                response.push_str(format!("  {fingerprint:016x}  {names}\n").as_str());
+            });
 
-                            if [c0, c1].iter().any(char::is_ascii_punctuation)
-                            {
-                                continue;
-                            }
+        if max_keys > 0
+        {
+            response.push_str(format!("Layouts that differ by at most {max_keys} keys:\n").as_str());
 
-                            let bigram = format!("{}{}", c0, c1);
-                            let freq = self.language_data.bigrams.get(&bigram).unwrap_or(&0.);
+            for (i, a) in names.iter().enumerate()
+            {
+                for b in names[i + 1 ..].iter()
+                {
+                    let distance = self.layouts[*a].distance(&self.layouts[*b]);
 
-                            v.push((bigram, freq));
-                        }
+                    if distance > 0 && distance <= max_keys
+                    {
+                        response.push_str(format!("  {a:24} {b:24} {distance} keys\n").as_str());
                     }
                 }
+            }
+        }
 
-                v.sort_by(|(_, f0), (_, f1)| f1.partial_cmp(f0).unwrap());
+        return response;
+    }
 
-                v.iter().take(top_n).for_each(|(s, f)| {
-                    response.push_str(format!("{} {:.5}\n", s, *f * 100.).as_str())
-                });
+    fn save(&mut self, o: Save) -> String
+    {
+        let layout = match self.layout_by_name(o.name.as_str())
+        {
+            | None =>
+            {
+                return format!("Layout \"{}\" does not exist.", o.name);
+            },
+            | Some(layout) => layout,
+        };
 
-                return response;
+        if self.layouts.contains_key(&o.new_name)
+        {
+            return format!("Layout \"{}\" already exists.", o.new_name);
+        }
+
+        if o.new_name.parse::<usize>().is_ok()
+        {
+            return format!("\"{}\" would select a layout of this session.", o.new_name);
+        }
+
+        let language = self.config.info.language.as_str();
+
+        return match DataFetch::save_layout(language, o.new_name.as_str(), &layout)
+        {
+            | Ok(path) =>
+            {
+                self.layouts.insert(o.new_name.clone(), layout);
+
+                format!("Saved \"{}\" to {}", o.name, path.display())
             },
+            | Err(e) => format!("Couldn't save \"{}\": {e}", o.new_name),
         };
     }
 
-    fn sfts(&self, o: Sfts) -> String
+    fn export(&self, o: Export) -> String
     {
-        return match self.layout_by_name(o.name.as_str())
+        let layout = match self.layout_by_name(o.name.as_str())
         {
             | None =>
             {
-                format!("Layout \"{}\" does not exist.", o.name)
+                return format!("Layout \"{}\" does not exist.", o.name);
             },
-            | Some(layout) =>
+            | Some(layout) => layout,
+        };
+
+        let exported = match o.format.to_lowercase().as_str()
+        {
+            | "xkb" =>
             {
-                let top_n = o.count.unwrap_or(10).min(48);
+                let language = self.config.info.language.as_str();
 
-                let mut response = format!("Top {top_n} SFTs for {}:\n", o.name);
+                let shift_pairs = match DataFetch::corpus_config(language)
+                {
+                    | Ok(c) => c.map(|c| c.shift_pairs()).unwrap_or_default(),
+                    | Err(e) => return e,
+                };
 
-                let mut v = Vec::new();
+                Xkb::symbols(o.name.as_str(), &layout, &shift_pairs)
+            },
+            | format @ ("qmk" | "zmk") =>
+            {
+                let keyboard_type = match layout.metadata.board.clone()
+                {
+                    | None => self.config.info.keyboard_type,
+                    | Some(board) => match KeyboardType::try_from(board)
+                    {
+                        | Ok(k) => k,
+                        | Err(e) => return e,
+                    },
+                };
 
-                for i in 0 .. 30
+                let templates = match DataFetch::matrix_templates(keyboard_type)
                 {
-                    for j in 0 .. 30
+                    | Err(e) => return e,
+                    | Ok(None) =>
                     {
-                        for k in 0 .. 30
-                        {
-                            if LayoutStats::is_sf(&mut [i as u8, j as u8, k as u8])
-                            {
-                                let c0 = layout.matrix[i];
-                                let c1 = layout.matrix[j];
-                                let c2 = layout.matrix[k];
-
-                                if [c0, c1, c2].iter().any(char::is_ascii_punctuation)
-                                {
-                                    continue;
-                                }
-
-                                let trigram = format!("{}{}{}", c0, c1, c2);
-                                let freq = self.language_data.trigrams.get(&trigram).unwrap_or(&0.);
-
-                                v.push((trigram, freq));
-                            }
-                        }
-                    }
+                        return format!("There is no matrix template for {keyboard_type} keyboards.");
+                    },
+                    | Ok(Some(t)) => t,
+                };
+
+                match format
+                {
+                    | "qmk" => Qmk::keymap(o.name.as_str(), &layout, &templates.qmk),
+                    | _ => Zmk::keymap(o.name.as_str(), &layout, &templates.zmk),
                 }
+            },
+            | _ =>
+            {
+                return format!(
+                    "\"{}\" is not a supported format, use xkb, qmk or zmk.",
+                    o.format
+                );
+            },
+        };
 
-                v.sort_by(|(_, f0), (_, f1)| f1.partial_cmp(f0).unwrap());
+        // The keymap lists them too, but that's easy to miss once it's written to a file.
+        let unsupported = match o.format.to_lowercase().as_str()
+        {
+            | "zmk" => Zmk::unsupported(&layout),
+            | _ => Vec::new(),
+        };
 
-                v.iter().take(top_n).for_each(|(s, f)| {
-                    response.push_str(format!("{} {:.5}\n", s, *f * 100.).as_str())
-                });
+        return match o.output
+        {
+            | None => exported,
+            | Some(path) => match std::fs::write(path.as_str(), exported)
+            {
+                | Ok(_) if !unsupported.is_empty() =>
+                {
+                    let keys = unsupported
+                        .iter()
+                        .map(|(c, layer, i)| format!("  {:8} {layer} {i}", c))
+                        .join("\n");
 
-                return response;
+                    format!(
+                        "Exported \"{}\" to {path}\nKeys without a ZMK keycode, left out:\n{keys}",
+                        o.name
+                    )
+                },
+                | Ok(_) => format!("Exported \"{}\" to {path}", o.name),
+                | Err(e) => format!("Couldn't write to {path}: {e}"),
+            },
+        };
+    }
+
+    fn import(&mut self, o: Import) -> String
+    {
+        if self.layouts.contains_key(&o.name)
+        {
+            return format!("Layout \"{}\" already exists.", o.name);
+        }
+
+        let report = match DataFetch::import_layout(o.path.as_str(), o.format.as_str())
+        {
+            | Ok(report) => report,
+            | Err(e) => return format!("Couldn't import {}: {e}", o.path),
+        };
+
+        let language = self.config.info.language.as_str();
+
+        return match DataFetch::save_layout(language, o.name.as_str(), &report.layout)
+        {
+            | Ok(path) =>
+            {
+                let response = format!("{report}\nSaved \"{}\" to {}", o.name, path.display());
+
+                self.layouts.insert(o.name, report.layout);
+
+                response
             },
+            | Err(e) => format!("Couldn't save \"{}\": {e}", o.name),
         };
     }
 
+    fn config(&self) -> String
+    {
+        let config = toml::to_string(&self.config).unwrap();
+
+        return format!("Loaded from {}\n\n{}", self.config_source, config.trim_end());
+    }
+
     pub fn ngram(&mut self, ngram: Ngram) -> String
     {
         let ngram = ngram.ngram;
+        let language_data = &self.data.language_data;
 
         return match ngram.chars().count()
         {
             | 1 =>
             {
                 let c = ngram.chars().next().unwrap();
-                let p = self.language_data.characters.get(&c).unwrap_or(&0.) * 100.;
+                let p = language_data.characters.get(&c).unwrap_or(&0.) * 100.;
 
                 format!("{ngram}: {p:.3}%")
             },
             | 2 =>
             {
                 let b0 = ngram.clone();
-                let p0 = self.language_data.bigrams.get(&b0).unwrap_or(&0.0) * 100.;
-                let s0 = self.language_data.skipgrams.get(&b0).unwrap_or(&0.0) * 100.;
+                let p0 = language_data.bigrams.get(&b0).unwrap_or(&0.0) * 100.;
+                let s0 = language_data.skipgrams.get(&b0).unwrap_or(&0.0) * 100.;
 
                 let temp = ngram.chars().collect_vec();
 
@@ -357,8 +828,8 @@ impl Repl
                 else
                 {
                     let b1: String = ngram.chars().rev().collect();
-                    let p1 = self.language_data.bigrams.get(&b1).unwrap_or(&0.0) * 100.;
-                    let s1 = self.language_data.skipgrams2.get(&b1).unwrap_or(&0.0) * 100.;
+                    let p1 = language_data.bigrams.get(&b1).unwrap_or(&0.0) * 100.;
+                    let s1 = language_data.skipgrams2.get(&b1).unwrap_or(&0.0) * 100.;
 
                     format!(
                         "[bigram]:   {:.5}%\n\
@@ -400,32 +871,121 @@ impl Repl
 
 impl Repl
 {
+    /// A layout by its name, or by its number if it was made in this session.
     fn layout_by_name(&self, name: &str) -> Option<Layout>
     {
-        return self.layouts.get(name).cloned();
+        return match name.parse::<usize>()
+        {
+            | Ok(number) => self.session.get(number).map(|(_, layout)| layout.clone()),
+            | Err(_) => self.layouts.get(name).cloned(),
+        };
     }
 
-    fn load_language(language: &str) -> LanguageData
+    fn pins_of(&self, name: &str) -> pins::Pins
     {
-        use std::io::Read;
-        let mut file = DataFetch::language_data_file(language);
-        let mut contents = String::new();
+        return self
+            .pins
+            .get(name)
+            .cloned()
+            .unwrap_or_else(|| self.config.pins.clone());
+    }
 
-        file.read_to_string(&mut contents).unwrap();
+    /// The optimizer chosen with `-o`, annealing if none was.
+    fn optimizer(name: Option<String>) -> Result<Optimizer, String>
+    {
+        return match name
+        {
+            | Some(name) => name.parse(),
+            | None => Ok(Optimizer::Annealing(Annealing::default())),
+        };
+    }
 
-        let language_data = serde_json::from_str(contents.as_str()).unwrap();
+    /// Optimizes `amount` layouts with the keys of `base` that aren't pinned, showing a progress
+    /// bar while it does. Best first.
+    fn run_generator(
+        &self,
+        base: &Layout,
+        pins: &pins::Pins,
+        amount: usize,
+        optimizer: &Optimizer,
+    ) -> Vec<ScoredLayout>
+    {
+        let generator = Generator::new(&self.data, &self.config);
 
-        return language_data;
+        let progress = Self::progress_bar(amount, "layouts");
+
+        let layouts = generator.generate(base, pins, amount, optimizer, || progress.inc(1));
+
+        progress.finish_and_clear();
+
+        return layouts;
     }
 
-    pub fn heat(c: char, p: f32) -> String
+    /// A progress bar over `length` of `unit`, with the time left.
+    fn progress_bar(length: usize, unit: &str) -> indicatif::ProgressBar
+    {
+        use indicatif::{
+            ProgressBar,
+            ProgressStyle,
+        };
+
+        let progress = ProgressBar::new(length as u64);
+        let template = format!("{{bar:40}} {{pos}}/{{len}} {unit}, {{eta}} left");
+
+        if let Ok(style) = ProgressStyle::with_template(template.as_str())
+        {
+            progress.set_style(style);
+        }
+
+        return progress;
+    }
+
+    /// The best `count` layouts that are distinct from `base` and each other, with their score.
+    /// They are added to the session, under the number shown above them.
+    fn show_best(
+        &mut self,
+        base: &Layout,
+        origin: &str,
+        layouts: Vec<ScoredLayout>,
+        count: usize,
+    ) -> String
+    {
+        return generation::distinct(base, layouts)
+            .into_iter()
+            .take(count)
+            .map(|l| {
+                let number = self.session.len();
+                let label = format!("{number}:");
+                let shown = self.show_numbered(label.as_str(), &l.layout, l.score.total());
+
+                self.session.push((origin.to_string(), l.layout));
+
+                shown
+            })
+            .join("\n\n");
+    }
+
+    /// A layout of the session below its label, coloured by the effort of its keys, with its
+    /// score.
+    fn show_numbered(&self, label: &str, layout: &Layout, score: f32) -> String
+    {
+        let keyboard_type = self.config.info.keyboard_type;
+        let heatmap = HeatmapStats::new(&self.data, layout, keyboard_type);
+        let grid = Self::heatmap(&heatmap.positions, &layout.matrix).join("\n");
+
+        return format!("{label}\n{grid}\nscore: {score:.3}");
+    }
+
+    /// Colours a key by its effort, the frequency of the key in percent times the effort of its
+    /// position.
+    pub fn heat(c: char, effort: f32) -> String
     {
         use ansi_rgb::{
             rgb,
             Colorable,
         };
 
-        let complement = 192. - p * 1720.;
+        let complement = 192. - effort * 12.;
         let complement = complement.max(0.) as u8;
 
         let heat = rgb(192, complement, complement);
@@ -435,7 +995,7 @@ impl Repl
         return format!("{formatted}");
     }
 
-    pub fn heatmap(data: &HashMap<char, f32>, chars: &Fixed<char>) -> Vec<String>
+    pub fn heatmap(efforts: &Fixed<f32>, chars: &Fixed<char>) -> Vec<String>
     {
         let mut map = Vec::new();
         let mut print_str = String::new();
@@ -454,9 +1014,7 @@ impl Repl
                 print_str.push(' ');
             }
 
-            let p = *data.get(c).unwrap_or(&0.0);
-
-            let heat = Self::heat(*c, p);
+            let heat = Self::heat(*c, efforts[i]);
 
             print_str.push_str(heat.as_str());
             print_str.push(' ');
@@ -466,70 +1024,4 @@ impl Repl
 
         return map;
     }
-
-    fn weight_stats(&self, stats: &mut LayoutStats)
-    {
-        for (k, mut v) in stats.bigram_stats.inner.iter_mut()
-        {
-            match k
-            {
-                | SFB =>
-                {},
-                | LSB =>
-                {},
-                | S1SFB =>
-                {},
-                | S2SFB =>
-                {},
-                | S3SFB =>
-                {},
-                | IRB =>
-                {},
-                | ORB =>
-                {},
-                | AB =>
-                {},
-                | Repeat =>
-                {},
-                | S =>
-                {},
-            }
-        }
-
-        for (k, v) in stats.trigram_stats.inner.iter()
-        {
-            match k
-            {
-                | SFT =>
-                {},
-                | IRT =>
-                {},
-                | ORT =>
-                {},
-                | Redirect =>
-                {},
-                | AT =>
-                {},
-            }
-        }
-
-        for (k, v) in stats.disjoint_stats.inner.iter()
-        {
-            match k
-            {
-                | D1S =>
-                {},
-                | D1IRB =>
-                {},
-                | D1ORB =>
-                {},
-                | D1SFB =>
-                {},
-                | D1LSB =>
-                {},
-                | D1Repeat =>
-                {},
-            }
-        }
-    }
 }